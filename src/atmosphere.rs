@@ -0,0 +1,251 @@
+// Atmósferas: una esfera algo mayor que el planeta donde se integra la dispersión simple
+// de Rayleigh y Mie de la luz del Sol a lo largo de cada rayo de la cámara.
+
+use serde::Deserialize;
+use wgpu::util::DeviceExt;
+
+use crate::culling::BoundingSphere;
+use crate::lod::SphereMesh;
+use crate::scene::BodyParams;
+use crate::shaders;
+use crate::Uniforms;
+
+// Los coeficientes se expresan por radio del planeta y las alturas en radios sobre la superficie
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct AtmosphereParams {
+    pub height: f32,          // Radio de la capa exterior, en radios del planeta
+    pub rayleigh: [f32; 3],   // Coeficientes de dispersión por color; definen el tono del cielo
+    pub rayleigh_height: f32, // Altura de escala de la densidad de Rayleigh
+    pub mie: f32,             // Coeficiente de la neblina de partículas grandes
+    pub mie_height: f32,
+    pub mie_g: f32, // Anisotropía de Mie: cuánto se concentra la luz hacia adelante
+    pub sun_intensity: f32,
+}
+
+impl Default for AtmosphereParams {
+    fn default() -> Self {
+        Self {
+            height: 1.06,
+            rayleigh: [5.0, 12.0, 30.0],
+            rayleigh_height: 0.02,
+            mie: 4.0,
+            mie_height: 0.01,
+            mie_g: 0.76,
+            sun_intensity: 12.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct AtmosphereUniforms {
+    rayleigh: [f32; 4], // Coeficientes (rgb) y altura de escala (a)
+    height: f32,
+    mie: f32,
+    mie_height: f32,
+    mie_g: f32,
+    sun_intensity: f32,
+    _padding: [f32; 3],
+}
+
+impl From<&AtmosphereParams> for AtmosphereUniforms {
+    fn from(params: &AtmosphereParams) -> Self {
+        let [r, g, b] = params.rayleigh;
+        Self {
+            rayleigh: [r, g, b, params.rayleigh_height.max(0.0001)],
+            height: params.height.max(1.001),
+            mie: params.mie,
+            mie_height: params.mie_height.max(0.0001),
+            mie_g: params.mie_g.clamp(-0.99, 0.99),
+            sun_intensity: params.sun_intensity,
+            _padding: [0.0; 3],
+        }
+    }
+}
+
+// Pipelines compartidos por las atmósferas de todos los cuerpos: `pipeline` dibuja la capa
+// desde afuera y `inside` cuando la cámara está dentro de ella
+pub struct AtmospherePipeline {
+    pub pipeline: wgpu::RenderPipeline,
+    pub inside: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl AtmospherePipeline {
+    // `shadow_layout` es el grupo 1 con las sombras del Sol, de donde sale su posición
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        shadow_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Atmosphere Bind Group Layout"),
+            entries: &[uniform_entry(0), uniform_entry(1)],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Atmosphere Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("atmosphere.wgsl", &[]).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Atmosphere Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, shadow_layout],
+            push_constant_ranges: &[],
+        });
+
+        let create_pipeline = |label, cull_mode| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<[f32; 3]>() as u64,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        // Alfa premultiplicado: suma la luz dispersada y atenúa lo que hay detrás
+                        blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: Some(cull_mode), // Cada rayo se integra una vez, desde una sola cara
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+                // La cara cercana de la capa queda delante del planeta, así que cubre también su disco
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+        };
+
+        Self {
+            pipeline: create_pipeline("Atmosphere Pipeline", wgpu::Face::Back),
+            // Desde adentro la cara cercana queda detrás de la cámara: se dibuja la lejana y el
+            // shader empieza a integrar en la cámara
+            inside: create_pipeline("Atmosphere Inside Pipeline", wgpu::Face::Front),
+            bind_group_layout,
+        }
+    }
+}
+
+pub struct Atmosphere {
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    uniforms: Uniforms,
+    radius: f32, // Radio de la capa exterior en el mundo
+    pub center: cgmath::Vector3<f32>,
+}
+
+impl Atmosphere {
+    pub fn new(device: &wgpu::Device, pipeline: &AtmospherePipeline, body: &BodyParams, params: &AtmosphereParams) -> Self {
+        let name = &body.name;
+        let atmosphere = AtmosphereUniforms::from(params);
+        let radius = body.scale * atmosphere.height;
+        let uniforms = Uniforms::new([1.0, 1.0, 1.0, 1.0], radius, 0.0, 0.0);
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Atmosphere Uniform Buffer", name)),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let atmosphere_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Atmosphere Buffer", name)),
+            contents: bytemuck::cast_slice(&[atmosphere]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Atmosphere Bind Group"),
+            layout: &pipeline.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: atmosphere_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            uniform_buffer,
+            bind_group,
+            uniforms,
+            radius,
+            center: cgmath::Vector3::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    // Sigue al planeta en `center`; la capa no gira, la dispersión no depende de la superficie
+    pub fn update(
+        &mut self,
+        queue: &wgpu::Queue,
+        view_proj: [[f32; 4]; 4],
+        camera_position: [f32; 4],
+        center: cgmath::Vector3<f32>,
+    ) {
+        self.center = center;
+        self.uniforms.view_proj = view_proj;
+        self.uniforms.camera_position = camera_position;
+        self.uniforms.model =
+            (cgmath::Matrix4::from_translation(center) * cgmath::Matrix4::from_scale(self.radius)).into();
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniforms]));
+    }
+
+    // Si la cámara del último update está dentro de la capa exterior
+    pub fn contains_camera(&self) -> bool {
+        let [x, y, z, _] = self.uniforms.camera_position;
+        cgmath::InnerSpace::magnitude(cgmath::Vector3::new(x, y, z) - self.center) < self.radius
+    }
+
+    // Esfera de la capa exterior, para el frustum culling
+    pub fn bounds(&self) -> BoundingSphere {
+        BoundingSphere::new(self.center, self.radius)
+    }
+
+    // `mesh` es el nivel de detalle del cuerpo al que rodea la capa
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, mesh: &'a SphereMesh) {
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        mesh.draw(render_pass);
+    }
+}
@@ -0,0 +1,453 @@
+// Cinturones de asteroides y de Kuiper: miles de rocas irregulares dibujadas con
+// instancing. La órbita y el giro de cada roca se calculan en el vertex shader.
+
+use std::collections::HashMap;
+
+use cgmath::{ElementWise, InnerSpace, Vector3};
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use wgpu::util::DeviceExt;
+
+use crate::culling::{BoundingSphere, CullCount, Frustum};
+use crate::shaders;
+
+// Formas de roca distintas; las instancias de cada cinturón se reparten entre ellas
+const ROCK_VARIANTS: u32 = 4;
+
+// Radio que encierra cualquier roca de tamaño 1; debe coincidir con ROCK_RADIUS de belts.wgsl
+const ROCK_RADIUS: f32 = 1.6;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BeltParams {
+    pub name: String,
+    pub count: u32,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub max_eccentricity: f32,
+    pub max_inclination: f32, // Grados
+    pub min_size: f32,
+    pub max_size: f32,
+    pub orbital_speed: f32, // En el borde interior; hacia afuera decrece como r^-1.5
+    pub colors: [[f32; 3]; 2],
+    pub ambient: f32,
+    pub seed: u64,
+}
+
+impl Default for BeltParams {
+    fn default() -> Self {
+        Self {
+            name: String::from("Cinturón"),
+            count: 20_000,
+            inner_radius: 10.0,
+            outer_radius: 12.0,
+            max_eccentricity: 0.05,
+            max_inclination: 3.0,
+            min_size: 0.02,
+            max_size: 0.07,
+            orbital_speed: 0.5,
+            colors: [[0.35, 0.3, 0.25], [0.55, 0.5, 0.45]],
+            ambient: 0.08,
+            seed: 1,
+        }
+    }
+}
+
+impl BeltParams {
+    // Rangos que usa generate_instances; con valores invertidos o negativos rand entraría en pánico
+    pub fn validate(&self) -> Result<(), String> {
+        let name = &self.name;
+        let values = [
+            self.inner_radius,
+            self.outer_radius,
+            self.max_eccentricity,
+            self.max_inclination,
+            self.min_size,
+            self.max_size,
+        ];
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(format!("{}: los radios, tamaños, excentricidad e inclinación deben ser finitos", name));
+        }
+        if self.inner_radius <= 0.0 {
+            return Err(format!("{}: inner_radius debe ser mayor que 0", name));
+        }
+        if self.inner_radius > self.outer_radius {
+            return Err(format!("{}: inner_radius no puede superar a outer_radius", name));
+        }
+        if self.min_size < 0.0 || self.min_size > self.max_size {
+            return Err(format!("{}: min_size debe estar entre 0 y max_size", name));
+        }
+        if !(0.0..1.0).contains(&self.max_eccentricity) {
+            return Err(format!("{}: max_eccentricity debe estar en [0, 1)", name));
+        }
+        if self.max_inclination < 0.0 {
+            return Err(format!("{}: max_inclination no puede ser negativa", name));
+        }
+        Ok(())
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct RockVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+// Parámetros fijos de cada roca; ver `RockInstance` en belts.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct RockInstance {
+    orbit: [f32; 4],
+    orientation: [f32; 4],
+    tumble: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BeltUniforms {
+    view_proj: [[f32; 4]; 4],
+    colors: [[f32; 4]; 2],
+    frustum: [[f32; 4]; 6], // Planos de la vista, para descartar rocas en el vertex shader
+    time: f32,
+    ambient: f32,
+    _padding: [f32; 2],
+}
+
+// Icosaedro subdividido una vez con abolladuras y estiramiento aleatorios.
+// Los vértices no se comparten para que cada cara tenga su propia normal (aspecto facetado).
+fn generate_rock(rng: &mut impl Rng) -> Vec<RockVertex> {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let mut points: Vec<Vector3<f32>> = [
+        [-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+        [0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+        [t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+    ]
+    .iter()
+    .map(|&[x, y, z]| Vector3::new(x, y, z).normalize())
+    .collect();
+    let faces: [[usize; 3]; 20] = [
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    // Cada arista se parte por la mitad una sola vez
+    let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut midpoint = |a: usize, b: usize, points: &mut Vec<Vector3<f32>>| {
+        *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+            points.push((points[a] + points[b]).normalize());
+            points.len() - 1
+        })
+    };
+    let mut triangles = Vec::new();
+    for [a, b, c] in faces {
+        let ab = midpoint(a, b, &mut points);
+        let bc = midpoint(b, c, &mut points);
+        let ca = midpoint(c, a, &mut points);
+        triangles.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+    }
+
+    // Abolladuras alrededor de direcciones al azar y un estiramiento distinto por eje
+    let dents: Vec<(Vector3<f32>, f32)> = (0..5).map(|_| (random_direction(rng), rng.gen_range(0.1..0.3))).collect();
+    let stretch = Vector3::new(rng.gen_range(0.9..1.4), rng.gen_range(0.6..0.9), rng.gen_range(0.7..1.1));
+    for point in &mut points {
+        let depth: f32 = dents
+            .iter()
+            .map(|&(direction, strength)| strength * point.dot(direction).max(0.0).powi(4))
+            .sum();
+        let jitter = rng.gen_range(0.92..1.08);
+        *point = (*point * (1.0 - depth) * jitter).mul_element_wise(stretch);
+    }
+
+    let mut vertices = Vec::with_capacity(triangles.len() * 3);
+    for [a, b, c] in triangles {
+        // Las caras deben mirar hacia afuera (antihorario) para el culling
+        let mut normal = (points[b] - points[a]).cross(points[c] - points[a]).normalize();
+        let mut corners = [a, b, c];
+        if normal.dot(points[a] + points[b] + points[c]) < 0.0 {
+            normal = -normal;
+            corners = [a, c, b];
+        }
+        for index in corners {
+            vertices.push(RockVertex {
+                position: points[index].into(),
+                normal: normal.into(),
+            });
+        }
+    }
+    vertices
+}
+
+fn random_direction(rng: &mut impl Rng) -> Vector3<f32> {
+    let z: f32 = rng.gen_range(-1.0..=1.0);
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    let ring = (1.0 - z * z).sqrt();
+    Vector3::new(ring * angle.cos(), ring * angle.sin(), z)
+}
+
+fn generate_instances(params: &BeltParams) -> Vec<RockInstance> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(params.seed);
+    let max_inclination = params.max_inclination.to_radians();
+    (0..params.count)
+        .map(|_| {
+            // Distribución uniforme por área dentro de la corona
+            let inner2 = params.inner_radius * params.inner_radius;
+            let outer2 = params.outer_radius * params.outer_radius;
+            let radius = rng.gen_range(inner2..=outer2).sqrt();
+            let speed = params.orbital_speed * (params.inner_radius / radius).powf(1.5);
+
+            let axis = random_direction(&mut rng);
+            RockInstance {
+                orbit: [
+                    radius,
+                    rng.gen_range(0.0..=params.max_eccentricity),
+                    rng.gen_range(0.0..std::f32::consts::TAU),
+                    speed,
+                ],
+                orientation: [
+                    rng.gen_range(-max_inclination..=max_inclination),
+                    rng.gen_range(0.0..std::f32::consts::TAU),
+                    rng.gen_range(params.min_size..=params.max_size),
+                    rng.gen_range(-2.0..2.0),
+                ],
+                tumble: [axis.x, axis.y, axis.z, rng.gen_range(0.0..1.0)],
+            }
+        })
+        .collect()
+}
+
+struct Belt {
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    instance_buffer: wgpu::Buffer,
+    count: u32,
+    uniforms: BeltUniforms,
+    bounds: BoundingSphere, // Encierra todas las órbitas del cinturón
+    visible: bool,
+}
+
+// Todos los cinturones de la escena con su pipeline y las mallas de roca compartidas
+pub struct AsteroidBelts {
+    pipeline: wgpu::RenderPipeline,
+    rock_buffer: wgpu::Buffer,
+    rock_vertices: u32, // Vértices de cada variante
+    belts: Vec<Belt>,
+}
+
+impl AsteroidBelts {
+    // `shadow_layout` es el grupo 1 con las sombras del Sol, de donde sale su posición
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        shadow_layout: &wgpu::BindGroupLayout,
+        belts: &[BeltParams],
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Belt Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Belt Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("belts.wgsl", &[]).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Belt Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, shadow_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Belt Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<RockVertex>() as u64,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+                    },
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<RockInstance>() as u64,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &wgpu::vertex_attr_array![2 => Float32x4, 3 => Float32x4, 4 => Float32x4],
+                    },
+                ],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        // Las variantes van una detrás de otra en el mismo buffer
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+        let variants: Vec<Vec<RockVertex>> = (0..ROCK_VARIANTS).map(|_| generate_rock(&mut rng)).collect();
+        let rock_vertices = variants[0].len() as u32;
+        let rock_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Rock Vertex Buffer"),
+            contents: bytemuck::cast_slice(&variants.concat()),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let belts = belts
+            .iter()
+            .map(|params| {
+                let [c0, c1] = params.colors;
+                let uniforms = BeltUniforms {
+                    view_proj: cgmath::Matrix4::from_scale(1.0).into(),
+                    colors: [[c0[0], c0[1], c0[2], 1.0], [c1[0], c1[1], c1[2], 1.0]],
+                    frustum: [[0.0; 4]; 6],
+                    time: 0.0,
+                    ambient: params.ambient,
+                    _padding: [0.0; 2],
+                };
+
+                let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("{} Uniform Buffer", params.name)),
+                    contents: bytemuck::cast_slice(&[uniforms]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some(&format!("{} Bind Group", params.name)),
+                    layout: &bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    }],
+                });
+
+                let instances = generate_instances(params);
+                let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("{} Instance Buffer", params.name)),
+                    contents: bytemuck::cast_slice(&instances),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+
+                Belt {
+                    uniform_buffer,
+                    bind_group,
+                    instance_buffer,
+                    count: instances.len() as u32,
+                    uniforms,
+                    bounds: BoundingSphere::new(
+                        Vector3::new(0.0, 0.0, 0.0),
+                        params.outer_radius * (1.0 + params.max_eccentricity) + params.max_size * ROCK_RADIUS,
+                    ),
+                    visible: true,
+                }
+            })
+            .collect();
+
+        Self {
+            pipeline,
+            rock_buffer,
+            rock_vertices,
+            belts,
+        }
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, view_proj: [[f32; 4]; 4], frustum: &Frustum, time: f32) {
+        for belt in &mut self.belts {
+            belt.uniforms.view_proj = view_proj;
+            belt.uniforms.frustum = frustum.planes();
+            belt.uniforms.time = time;
+            queue.write_buffer(&belt.uniform_buffer, 0, bytemuck::cast_slice(&[belt.uniforms]));
+        }
+    }
+
+    // Cinturones enteros fuera de la vista; cada roca de los visibles se descarta en la GPU
+    pub fn cull(&mut self, frustum: &Frustum) -> CullCount {
+        let mut count = CullCount::default();
+        for belt in &mut self.belts {
+            belt.visible = count.record(frustum.intersects(&belt.bounds));
+        }
+        count
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.belts.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.rock_buffer.slice(..));
+        for belt in self.belts.iter().filter(|belt| belt.visible) {
+            render_pass.set_bind_group(0, &belt.bind_group, &[]);
+            render_pass.set_vertex_buffer(1, belt.instance_buffer.slice(..));
+
+            // Cada variante de roca dibuja una parte contigua de las instancias
+            let per_variant = belt.count.div_ceil(ROCK_VARIANTS);
+            for variant in 0..ROCK_VARIANTS {
+                let first = (variant * per_variant).min(belt.count);
+                let last = ((variant + 1) * per_variant).min(belt.count);
+                let vertices = variant * self.rock_vertices..(variant + 1) * self.rock_vertices;
+                render_pass.draw(vertices, first..last);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_ranges_that_rand_cannot_sample() {
+        assert!(BeltParams::default().validate().is_ok());
+        let invalid = [
+            BeltParams { inner_radius: 0.0, ..Default::default() },
+            BeltParams { inner_radius: 13.0, outer_radius: 12.0, ..Default::default() },
+            BeltParams { min_size: 0.1, max_size: 0.05, ..Default::default() },
+            BeltParams { max_eccentricity: -0.1, ..Default::default() },
+            BeltParams { max_inclination: -1.0, ..Default::default() },
+            BeltParams { outer_radius: f32::NAN, ..Default::default() },
+        ];
+        for params in invalid {
+            let error = params.validate().unwrap_err();
+            assert!(error.starts_with("Cinturón:"), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// Capa de nubes: una esfera translúcida algo mayor que el planeta que gira con su propio
+// período, de modo que las nubes se desplazan respecto a la superficie y le dan sombra.
+
+use serde::Deserialize;
+use wgpu::util::DeviceExt;
+
+use crate::culling::BoundingSphere;
+use crate::lod::SphereMesh;
+use crate::scene::BodyParams;
+use crate::shaders;
+use crate::Uniforms;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CloudParams {
+    pub height: f32, // Radio de la capa en radios del planeta
+    pub color: [f32; 3],
+    pub coverage: f32,        // 0 = sin nubes, 1 = cubierto
+    pub opacity: f32,         // Opacidad de las nubes más densas
+    pub rotation_period: f32, // Segundos por vuelta; distinto al del planeta para que deriven
+    pub shadow: f32,          // Cuánto oscurecen el suelo (0 = sin sombra)
+    pub seed: u32,
+}
+
+impl Default for CloudParams {
+    fn default() -> Self {
+        Self {
+            height: 1.015,
+            color: [1.0, 1.0, 1.0],
+            coverage: 0.3,
+            opacity: 0.9,
+            rotation_period: 0.0,
+            shadow: 0.6,
+            seed: 0,
+        }
+    }
+}
+
+// Velocidad angular en radianes por segundo; como en el grafo de escena, período 0 = sin giro
+pub fn angular_speed(rotation_period: f32) -> f32 {
+    if rotation_period != 0.0 {
+        std::f32::consts::TAU / rotation_period
+    } else {
+        0.0
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct CloudUniforms {
+    color: [f32; 4],
+    coverage: f32,
+    opacity: f32,
+    seed: u32,
+    _padding: u32,
+}
+
+impl From<&CloudParams> for CloudUniforms {
+    fn from(params: &CloudParams) -> Self {
+        let [r, g, b] = params.color;
+        Self {
+            color: [r, g, b, 1.0],
+            coverage: params.coverage.clamp(0.0, 1.0),
+            opacity: params.opacity.clamp(0.0, 1.0),
+            seed: params.seed,
+            _padding: 0,
+        }
+    }
+}
+
+// Pipeline compartido por las capas de nubes de todos los cuerpos
+pub struct CloudPipeline {
+    pub pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl CloudPipeline {
+    // `shadow_layout` es el grupo 1 con las sombras del Sol
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        shadow_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Cloud Bind Group Layout"),
+            entries: &[uniform_entry(0), uniform_entry(1)],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Cloud Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("clouds.wgsl", &[]).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Cloud Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, shadow_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Cloud Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<[f32; 3]>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back), // La cara trasera queda oculta por el planeta
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+        }
+    }
+}
+
+pub struct Clouds {
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    uniforms: Uniforms,
+    tilt: cgmath::Matrix4<f32>,
+    radius: f32, // Radio de la capa en el mundo
+    angular_speed: f32,
+    pub center: cgmath::Vector3<f32>,
+}
+
+impl Clouds {
+    pub fn new(device: &wgpu::Device, pipeline: &CloudPipeline, body: &BodyParams, params: &CloudParams) -> Self {
+        let name = &body.name;
+        let radius = body.scale * params.height.max(1.001);
+        let uniforms = Uniforms::new([1.0, 1.0, 1.0, 1.0], radius, 0.0, 0.0);
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Cloud Uniform Buffer", name)),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let cloud_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Cloud Buffer", name)),
+            contents: bytemuck::cast_slice(&[CloudUniforms::from(params)]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Cloud Bind Group"),
+            layout: &pipeline.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: cloud_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            uniform_buffer,
+            bind_group,
+            uniforms,
+            // Gira alrededor del mismo eje que el planeta
+            tilt: cgmath::Matrix4::from_angle_z(cgmath::Deg(body.axial_tilt)),
+            radius,
+            angular_speed: angular_speed(params.rotation_period),
+            center: cgmath::Vector3::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    // Sigue al planeta en `center` y gira con su propio período
+    pub fn update(
+        &mut self,
+        queue: &wgpu::Queue,
+        view_proj: [[f32; 4]; 4],
+        camera_position: [f32; 4],
+        center: cgmath::Vector3<f32>,
+        time: f32,
+    ) {
+        self.center = center;
+        self.uniforms.view_proj = view_proj;
+        self.uniforms.camera_position = camera_position;
+        self.uniforms.time = time;
+        self.uniforms.model = (cgmath::Matrix4::from_translation(center)
+            * self.tilt
+            * cgmath::Matrix4::from_angle_y(cgmath::Rad(time * self.angular_speed))
+            * cgmath::Matrix4::from_scale(self.radius))
+        .into();
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniforms]));
+    }
+
+    // Esfera de la capa, para el frustum culling
+    pub fn bounds(&self) -> BoundingSphere {
+        BoundingSphere::new(self.center, self.radius)
+    }
+
+    // `mesh` es el nivel de detalle del cuerpo al que rodea la capa
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, mesh: &'a SphereMesh) {
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        mesh.draw(render_pass);
+    }
+}
@@ -0,0 +1,338 @@
+// Cometas en órbitas muy excéntricas. La coma y las colas son partículas dibujadas como
+// billboards con mezcla aditiva; su recorrido se calcula en el vertex shader a partir de la
+// dirección del Sol y de la velocidad del núcleo, que se actualizan en cada frame.
+
+use cgmath::{InnerSpace, Matrix4, Vector3};
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use wgpu::util::DeviceExt;
+
+use crate::culling::{BoundingSphere, CullCount, Frustum};
+use crate::lines::LineVertex;
+use crate::orbits::Orbit;
+use crate::shaders;
+
+// Tipos de partícula; deben coincidir con las constantes de comets.wgsl
+const PARTICLE_COMA: u32 = 0;
+const PARTICLE_ION: u32 = 1;
+const PARTICLE_DUST: u32 = 2;
+
+// Fracción de las partículas que forma la cola de iones; el resto es polvo
+const ION_FRACTION: f32 = 0.4;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CometParams {
+    pub name: String,
+    pub orbital_radius: f32, // Semieje mayor
+    pub orbital_speed: f32,
+    pub eccentricity: f32,
+    pub inclination: f32, // Grados
+    pub periapsis: f32,   // Grados
+    pub coma_size: f32,   // Radio de la coma en el perihelio
+    pub tail_length: f32, // Longitud de las colas en el perihelio
+    pub ion_color: [f32; 3],
+    pub dust_color: [f32; 3],
+    pub particle_count: u32,
+    pub seed: u64,
+}
+
+impl Default for CometParams {
+    fn default() -> Self {
+        Self {
+            name: String::from("Cometa"),
+            orbital_radius: 20.0,
+            orbital_speed: 0.05,
+            eccentricity: 0.7,
+            inclination: 10.0,
+            periapsis: 0.0,
+            coma_size: 0.3,
+            tail_length: 6.0,
+            ion_color: [0.35, 0.6, 1.0],
+            dust_color: [1.0, 0.85, 0.6],
+            particle_count: 4000,
+            seed: 1,
+        }
+    }
+}
+
+// Parámetros fijos de cada partícula; ver `Particle` en comets.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ParticleInstance {
+    kind: u32,
+    phase: f32,       // Desfase a lo largo de la cola
+    offset: [f32; 2], // Desplazamiento lateral respecto al eje de la cola
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct CometUniforms {
+    view_proj: [[f32; 4]; 4],
+    camera_right: [f32; 4],
+    camera_up: [f32; 4],
+    nucleus: [f32; 4],  // Posición (xyz) y radio de la coma (w)
+    anti_sun: [f32; 4], // Dirección opuesta al Sol (xyz) y longitud de las colas (w)
+    wake: [f32; 4],     // Dirección opuesta a la velocidad (xyz) y actividad (w)
+    ion_color: [f32; 4],
+    dust_color: [f32; 4],
+    time: f32,
+    _padding: [f32; 3],
+}
+
+fn generate_particles(params: &CometParams) -> Vec<ParticleInstance> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(params.seed);
+    let ion_count = (params.particle_count as f32 * ION_FRACTION) as u32;
+
+    // La primera instancia es el resplandor de la coma
+    let mut particles = vec![ParticleInstance {
+        kind: PARTICLE_COMA,
+        phase: 0.0,
+        offset: [0.0, 0.0],
+    }];
+    particles.extend((0..params.particle_count).map(|i| {
+        // Desplazamiento dentro de un disco, más denso hacia el eje
+        let radius = rng.gen_range(0.0f32..1.0).powi(2);
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        ParticleInstance {
+            kind: if i < ion_count { PARTICLE_ION } else { PARTICLE_DUST },
+            phase: rng.gen_range(0.0..1.0),
+            offset: [radius * angle.cos(), radius * angle.sin()],
+        }
+    }));
+    particles
+}
+
+struct Comet {
+    orbit: Orbit,
+    coma_size: f32,
+    tail_length: f32,
+    orbit_color: [f32; 4],
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    instance_buffer: wgpu::Buffer,
+    count: u32,
+    uniforms: CometUniforms,
+    bounds: BoundingSphere, // Núcleo, coma y colas en el último `update`
+    visible: bool,
+}
+
+// Todos los cometas de la escena con su pipeline aditivo
+pub struct Comets {
+    pipeline: wgpu::RenderPipeline,
+    comets: Vec<Comet>,
+}
+
+impl Comets {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32, comets: &[CometParams]) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Comet Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Comet Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("comets.wgsl", &[]).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Comet Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Comet Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                // Cada partícula es un quad de seis vértices generado a partir de vertex_index
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<ParticleInstance>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![0 => Uint32, 1 => Float32, 2 => Float32x2],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    // Mezcla aditiva: el orden de las partículas no importa
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Zero,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            // Los planetas tapan las colas, pero las partículas no escriben profundidad
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let comets = comets
+            .iter()
+            .map(|params| {
+                let rgba = |[r, g, b]: [f32; 3]| [r, g, b, 1.0];
+                let uniforms = CometUniforms {
+                    view_proj: Matrix4::from_scale(1.0).into(),
+                    camera_right: [1.0, 0.0, 0.0, 0.0],
+                    camera_up: [0.0, 1.0, 0.0, 0.0],
+                    nucleus: [0.0; 4],
+                    anti_sun: [1.0, 0.0, 0.0, 0.0],
+                    wake: [0.0, 0.0, 1.0, 0.0],
+                    ion_color: rgba(params.ion_color),
+                    dust_color: rgba(params.dust_color),
+                    time: 0.0,
+                    _padding: [0.0; 3],
+                };
+
+                let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("{} Uniform Buffer", params.name)),
+                    contents: bytemuck::cast_slice(&[uniforms]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some(&format!("{} Bind Group", params.name)),
+                    layout: &bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    }],
+                });
+
+                let particles = generate_particles(params);
+                let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("{} Instance Buffer", params.name)),
+                    contents: bytemuck::cast_slice(&particles),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+
+                let [r, g, b] = params.ion_color;
+                Comet {
+                    orbit: Orbit::new(
+                        params.orbital_radius,
+                        params.orbital_speed,
+                        params.eccentricity,
+                        params.inclination,
+                        params.periapsis,
+                    ),
+                    coma_size: params.coma_size,
+                    tail_length: params.tail_length,
+                    orbit_color: [r, g, b, 0.35],
+                    uniform_buffer,
+                    bind_group,
+                    instance_buffer,
+                    count: particles.len() as u32,
+                    uniforms,
+                    bounds: BoundingSphere::new(Vector3::new(0.0, 0.0, 0.0), 0.0),
+                    visible: true,
+                }
+            })
+            .collect();
+
+        Self { pipeline, comets }
+    }
+
+    // Mueve cada núcleo por su órbita y orienta las colas según el Sol (en el origen).
+    // La actividad crece con el inverso del cuadrado de la distancia: vale 1 en el perihelio.
+    pub fn update(&mut self, queue: &wgpu::Queue, view: Matrix4<f32>, proj: Matrix4<f32>, time: f32) {
+        for comet in &mut self.comets {
+            let position = comet.orbit.position(time);
+            let velocity = comet.orbit.position(time + 0.01) - position;
+            let distance = position.magnitude().max(0.001);
+            let activity = (comet.orbit.periapsis_distance() / distance).powi(2).min(1.0);
+
+            let anti_sun = position / distance;
+            let wake = if velocity.magnitude2() > 0.0 {
+                -velocity.normalize()
+            } else {
+                anti_sun
+            };
+
+            let uniforms = &mut comet.uniforms;
+            uniforms.view_proj = (proj * view).into();
+            // Filas de la rotación de la vista: ejes de la cámara en el mundo
+            uniforms.camera_right = [view.x.x, view.y.x, view.z.x, 0.0];
+            uniforms.camera_up = [view.x.y, view.y.y, view.z.y, 0.0];
+            uniforms.nucleus = position.extend(comet.coma_size * activity.sqrt()).into();
+            uniforms.anti_sun = anti_sun.extend(comet.tail_length * activity).into();
+            uniforms.wake = wake.extend(activity).into();
+            uniforms.time = time;
+            queue.write_buffer(&comet.uniform_buffer, 0, bytemuck::cast_slice(&[*uniforms]));
+
+            // Alcance de las partículas de comets.wgsl: la coma alrededor del núcleo y las
+            // colas hasta su largo, curvadas y abiertas, más el tamaño de los quads
+            let coma = comet.coma_size * activity.sqrt() * 2.5 * std::f32::consts::SQRT_2;
+            let tail = comet.tail_length * activity;
+            comet.bounds = BoundingSphere::new(position, coma.max(tail * 1.8) + 0.25);
+        }
+    }
+
+    pub fn cull(&mut self, frustum: &Frustum) -> CullCount {
+        let mut count = CullCount::default();
+        for comet in &mut self.comets {
+            comet.visible = count.record(frustum.intersects(&comet.bounds));
+        }
+        count
+    }
+
+    // Elipse completa de cada cometa alrededor del Sol
+    pub fn append_orbits(&self, vertices: &mut Vec<LineVertex>) {
+        for comet in &self.comets {
+            comet.orbit.append_path(Vector3::new(0.0, 0.0, 0.0), comet.orbit_color, vertices);
+        }
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.comets.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        for comet in self.comets.iter().filter(|comet| comet.visible) {
+            render_pass.set_bind_group(0, &comet.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, comet.instance_buffer.slice(..));
+            render_pass.draw(0..6, 0..comet.count);
+        }
+    }
+}
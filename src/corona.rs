@@ -0,0 +1,226 @@
+// Corona de las estrellas: un billboard orientado hacia la cámara alrededor del cuerpo, con
+// rayos que fluyen hacia afuera y protuberancias que aparecen de vez en cuando en el limbo.
+
+use cgmath::Matrix4;
+use serde::Deserialize;
+use wgpu::util::DeviceExt;
+
+use crate::culling::BoundingSphere;
+use crate::scene::BodyParams;
+use crate::shaders;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CoronaParams {
+    pub radius: f32, // Extensión del halo en radios del cuerpo
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub streamers: f32, // Cantidad aproximada de rayos alrededor del disco
+    pub prominence_color: [f32; 3],
+    pub prominences: u32,       // Máximo de protuberancias activas a la vez
+    pub prominence_height: f32, // Altura máxima sobre la superficie, en radios del cuerpo
+    pub seed: u32,
+}
+
+impl Default for CoronaParams {
+    fn default() -> Self {
+        Self {
+            radius: 3.0,
+            color: [1.0, 0.85, 0.65],
+            intensity: 1.0,
+            streamers: 10.0,
+            prominence_color: [3.0, 0.7, 0.35],
+            prominences: 4,
+            prominence_height: 0.3,
+            seed: 0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct CoronaUniforms {
+    view_proj: [[f32; 4]; 4],
+    camera_right: [f32; 4],
+    camera_up: [f32; 4],
+    center: [f32; 4],           // Centro del cuerpo (xyz) y su radio (w)
+    color: [f32; 4],            // Color (rgb) e intensidad (a)
+    prominence_color: [f32; 4], // Color (rgb) y altura máxima (a)
+    extent: f32,
+    streamers: f32,
+    prominences: u32,
+    seed: u32,
+    time: f32,
+    _padding: [f32; 3],
+}
+
+// Pipeline compartido por las coronas de todas las estrellas
+pub struct CoronaPipeline {
+    pub pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl CoronaPipeline {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Corona Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Corona Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("corona.wgsl", &[]).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Corona Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Corona Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[], // Quad de seis vértices generado a partir de vertex_index
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    // Luz emitida: se suma a lo que haya detrás
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Zero,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            // El disco de la estrella y los planetas que pasan delante tapan el halo
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+        }
+    }
+}
+
+pub struct Corona {
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    uniforms: CoronaUniforms,
+}
+
+impl Corona {
+    pub fn new(device: &wgpu::Device, pipeline: &CoronaPipeline, body: &BodyParams, params: &CoronaParams) -> Self {
+        let [r, g, b] = params.color;
+        let [pr, pg, pb] = params.prominence_color;
+        let uniforms = CoronaUniforms {
+            view_proj: Matrix4::from_scale(1.0).into(),
+            camera_right: [1.0, 0.0, 0.0, 0.0],
+            camera_up: [0.0, 1.0, 0.0, 0.0],
+            center: [0.0, 0.0, 0.0, body.scale],
+            color: [r, g, b, params.intensity],
+            prominence_color: [pr, pg, pb, params.prominence_height],
+            extent: params.radius.max(1.0),
+            streamers: params.streamers,
+            prominences: params.prominences,
+            seed: params.seed,
+            time: 0.0,
+            _padding: [0.0; 3],
+        };
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Corona Uniform Buffer", body.name)),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Corona Bind Group"),
+            layout: &pipeline.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            uniform_buffer,
+            bind_group,
+            uniforms,
+        }
+    }
+
+    // Sigue a la estrella en `center` y se orienta hacia la cámara
+    pub fn update(
+        &mut self,
+        queue: &wgpu::Queue,
+        view: Matrix4<f32>,
+        proj: Matrix4<f32>,
+        center: cgmath::Vector3<f32>,
+        time: f32,
+    ) {
+        self.uniforms.view_proj = (proj * view).into();
+        // Filas de la rotación de la vista: ejes de la cámara en el mundo
+        self.uniforms.camera_right = [view.x.x, view.y.x, view.z.x, 0.0];
+        self.uniforms.camera_up = [view.x.y, view.y.y, view.z.y, 0.0];
+        self.uniforms.center = [center.x, center.y, center.z, self.uniforms.center[3]];
+        self.uniforms.time = time;
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniforms]));
+    }
+
+    // Esfera que encierra el billboard: su medio lado es `extent` radios de la estrella
+    pub fn bounds(&self) -> BoundingSphere {
+        let [x, y, z, radius] = self.uniforms.center;
+        BoundingSphere::new(cgmath::Vector3::new(x, y, z), radius * self.uniforms.extent * std::f32::consts::SQRT_2)
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
@@ -0,0 +1,163 @@
+// Frustum culling: cada cosa que se dibuja tiene una esfera que la encierra y, en cada frame,
+// las que quedan fuera de la pirámide de visión de la cámara no generan draw calls. Las
+// cuentas de dibujados y descartados se muestran en el título con la tecla I.
+
+use cgmath::{InnerSpace, Matrix, Matrix4, Vector3, Vector4};
+
+#[derive(Clone, Copy, Debug)]
+pub struct BoundingSphere {
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vector3<f32>, radius: f32) -> Self {
+        Self { center, radius }
+    }
+}
+
+// Los seis planos de la pirámide de visión, con la normal hacia adentro
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    // Planos de la matriz vista-proyección (Gribb y Hartmann) con el recorte de wgpu:
+    // -w ≤ x, y ≤ w y 0 ≤ z ≤ w, que es el que aplica la GPU
+    pub fn from_matrix(view_proj: Matrix4<f32>) -> Self {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|i| view_proj.row(i));
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2].map(|plane| plane / plane.truncate().magnitude());
+        Self { planes }
+    }
+
+    // Una esfera queda afuera solo si está por completo detrás de algún plano
+    pub fn intersects(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+
+    // Planos listos para un uniform, para descartar instancias en el vertex shader
+    pub fn planes(&self) -> [[f32; 4]; 6] {
+        self.planes.map(Into::into)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CullCount {
+    pub drawn: u32,
+    pub culled: u32,
+}
+
+impl CullCount {
+    // Cuenta el resultado y lo devuelve, para usarlo al decidir si se dibuja
+    pub fn record(&mut self, visible: bool) -> bool {
+        if visible {
+            self.drawn += 1;
+        } else {
+            self.culled += 1;
+        }
+        visible
+    }
+}
+
+// Cuentas del último frame por tipo de objeto
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CullingStats {
+    pub bodies: CullCount,
+    pub rings: CullCount,
+    pub atmospheres: CullCount,
+    pub clouds: CullCount,
+    pub coronas: CullCount,
+    pub belts: CullCount,
+    pub comets: CullCount,
+    pub ship: CullCount,
+}
+
+impl CullingStats {
+    pub fn total(&self) -> CullCount {
+        let counts = [
+            self.bodies,
+            self.rings,
+            self.atmospheres,
+            self.clouds,
+            self.coronas,
+            self.belts,
+            self.comets,
+            self.ship,
+        ];
+        counts.iter().fold(CullCount::default(), |total, count| CullCount {
+            drawn: total.drawn + count.drawn,
+            culled: total.culled + count.culled,
+        })
+    }
+
+    // Resumen para el título de la ventana: dibujados/total por tipo
+    pub fn summary(&self) -> String {
+        let entry = |name: &str, count: CullCount| format!("{} {}/{}", name, count.drawn, count.drawn + count.culled);
+        let total = self.total();
+        format!(
+            "Dibujados {} · descartados {} | {} · {} · {} · {} · {} · {} · {} · {}",
+            total.drawn,
+            total.culled,
+            entry("cuerpos", self.bodies),
+            entry("anillos", self.rings),
+            entry("atmósferas", self.atmospheres),
+            entry("nubes", self.clouds),
+            entry("coronas", self.coronas),
+            entry("cinturones", self.belts),
+            entry("cometas", self.comets),
+            entry("nave", self.ship),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cámara en el origen mirando hacia -z con `cgmath::perspective`, igual que `camera_projection`:
+    // su profundidad es la de OpenGL (-w ≤ z ≤ w), pero los planos se sacan con el recorte
+    // 0 ≤ z ≤ w que aplica la GPU, así que el plano cercano efectivo queda a casi 2·near
+    fn frustum() -> Frustum {
+        let view = Matrix4::look_at_rh(
+            cgmath::Point3::new(0.0, 0.0, 0.0),
+            cgmath::Point3::new(0.0, 0.0, -1.0),
+            Vector3::unit_y(),
+        );
+        let proj = cgmath::perspective(cgmath::Deg(60.0), 1.0, 0.1, 100.0);
+        Frustum::from_matrix(proj * view)
+    }
+
+    #[test]
+    fn keeps_what_the_camera_sees() {
+        let frustum = frustum();
+        assert!(frustum.intersects(&BoundingSphere::new(Vector3::new(0.0, 0.0, -10.0), 1.0)));
+        // Detrás de la cámara, más allá del plano lejano o muy a un costado
+        assert!(!frustum.intersects(&BoundingSphere::new(Vector3::new(0.0, 0.0, 10.0), 1.0)));
+        assert!(!frustum.intersects(&BoundingSphere::new(Vector3::new(0.0, 0.0, -150.0), 1.0)));
+        assert!(!frustum.intersects(&BoundingSphere::new(Vector3::new(30.0, 0.0, -10.0), 1.0)));
+    }
+
+    #[test]
+    fn spheres_crossing_a_plane_are_kept() {
+        let frustum = frustum();
+        // El borde derecho a 10 de distancia está en x = 10·tan(30°) ≈ 5.77
+        assert!(frustum.intersects(&BoundingSphere::new(Vector3::new(6.5, 0.0, -10.0), 1.0)));
+        assert!(!frustum.intersects(&BoundingSphere::new(Vector3::new(7.5, 0.0, -10.0), 1.0)));
+        // Una esfera que rodea a la cámara siempre se ve
+        assert!(frustum.intersects(&BoundingSphere::new(Vector3::new(0.0, 0.0, 5.0), 6.0)));
+    }
+
+    #[test]
+    fn stats_add_up() {
+        let mut stats = CullingStats::default();
+        assert!(stats.bodies.record(true));
+        assert!(!stats.bodies.record(false));
+        stats.ship.record(true);
+        let total = stats.total();
+        assert_eq!((total.drawn, total.culled), (2, 1));
+        assert!(stats.summary().starts_with("Dibujados 2 · descartados 1"));
+    }
+}
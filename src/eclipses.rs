@@ -0,0 +1,337 @@
+// Predicción de eclipses, ocultaciones y tránsitos: recorre un intervalo de tiempo con el
+// modelo de órbitas y detecta cuándo un cuerpo pasa delante de otro visto desde el centro
+// de un cuerpo observador. No necesita ventana ni GPU.
+
+use cgmath::InnerSpace;
+
+use crate::material::MaterialKind;
+use crate::scene::Scene;
+use crate::scene_graph::SceneGraph;
+
+// Iteraciones de bisección para afinar el inicio y el fin de cada evento
+const REFINE_ITERATIONS: u32 = 16;
+
+// Parámetros de la búsqueda (opciones --eclipses, --eclipse-from, --eclipse-to, ...)
+#[derive(Clone, Debug)]
+pub struct EclipseQuery {
+    pub observer: String,
+    pub start: f32,
+    pub end: f32,
+    pub step: f32,              // Paso del recorrido; los eventos más breves pueden perderse
+    pub jump_to: Option<usize>, // Evento (desde 1) al que saltar abriendo la ventana
+}
+
+impl Default for EclipseQuery {
+    fn default() -> Self {
+        Self {
+            observer: String::new(),
+            start: 0.0,
+            end: 300.0,
+            step: 0.05,
+            jump_to: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    Eclipse,     // Un cuerpo pasa delante de una estrella
+    Occultation, // El cuerpo de adelante se ve mayor que el de atrás y puede cubrirlo
+    Transit,     // El cuerpo de adelante se ve menor y cruza el disco del de atrás
+}
+
+impl EventKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            EventKind::Eclipse => "Eclipse",
+            EventKind::Occultation => "Ocultación",
+            EventKind::Transit => "Tránsito",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub kind: EventKind,
+    pub front: usize, // Nodo que queda delante
+    pub back: usize,  // Nodo tapado
+    pub start: f32,
+    pub peak: f32, // Instante de máxima cobertura (con la resolución del paso)
+    pub end: f32,
+    pub coverage: f32, // Fracción máxima del disco de atrás que queda tapada
+}
+
+pub struct Prediction {
+    pub observer: usize,
+    pub events: Vec<Event>,
+}
+
+// Disco aparente de un cuerpo visto desde el observador
+struct Disk {
+    direction: cgmath::Vector3<f32>,
+    radius: f32, // Radio angular
+    distance: f32,
+}
+
+fn disk(graph: &SceneGraph, observer: usize, index: usize) -> Disk {
+    let nodes = graph.nodes();
+    let offset = nodes[index].position - nodes[observer].position;
+    let distance = offset.magnitude().max(1e-6);
+    Disk {
+        direction: offset / distance,
+        radius: (nodes[index].scale / distance).min(1.0).asin(),
+        distance,
+    }
+}
+
+// Área común de dos círculos de radios `a` y `b` cuyos centros están a `d`
+fn overlap_area(a: f32, b: f32, d: f32) -> f32 {
+    use std::f32::consts::PI;
+    if d >= a + b {
+        return 0.0;
+    }
+    if d <= (a - b).abs() {
+        return PI * a.min(b).powi(2);
+    }
+    let alpha = ((d * d + a * a - b * b) / (2.0 * d * a)).clamp(-1.0, 1.0).acos();
+    let beta = ((d * d + b * b - a * a) / (2.0 * d * b)).clamp(-1.0, 1.0).acos();
+    let kite = ((-d + a + b) * (d + a - b) * (d - a + b) * (d + a + b)).max(0.0).sqrt();
+    a * a * alpha + b * b * beta - 0.5 * kite
+}
+
+// Dos discos que se superponen vistos desde el observador
+struct Overlap {
+    front: usize,
+    back: usize,
+    coverage: f32,      // Fracción tapada del disco de atrás
+    front_larger: bool, // El de adelante se ve mayor que el de atrás
+}
+
+fn overlap(graph: &SceneGraph, observer: usize, i: usize, j: usize) -> Option<Overlap> {
+    let (disk_i, disk_j) = (disk(graph, observer, i), disk(graph, observer, j));
+    let separation = disk_i.direction.dot(disk_j.direction).clamp(-1.0, 1.0).acos();
+    if separation >= disk_i.radius + disk_j.radius {
+        return None;
+    }
+    let (front, front_disk, back, back_disk) = if disk_i.distance < disk_j.distance {
+        (i, disk_i, j, disk_j)
+    } else {
+        (j, disk_j, i, disk_i)
+    };
+    let back_area = std::f32::consts::PI * back_disk.radius * back_disk.radius;
+    Some(Overlap {
+        front,
+        back,
+        coverage: (overlap_area(front_disk.radius, back_disk.radius, separation) / back_area).min(1.0),
+        front_larger: front_disk.radius >= back_disk.radius,
+    })
+}
+
+// Instante en que cambia la superposición de `i` y `j` entre `before` y `after`
+fn refine(graph: &mut SceneGraph, observer: usize, (i, j): (usize, usize), before: f32, after: f32) -> f32 {
+    let overlapping_after = {
+        graph.update(after);
+        overlap(graph, observer, i, j).is_some()
+    };
+    let (mut low, mut high) = (before, after);
+    for _ in 0..REFINE_ITERATIONS {
+        let middle = 0.5 * (low + high);
+        graph.update(middle);
+        if overlap(graph, observer, i, j).is_some() == overlapping_after {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    0.5 * (low + high)
+}
+
+pub fn predict(scene: &Scene, query: &EclipseQuery) -> Result<Prediction, String> {
+    let mut graph = SceneGraph::new(&scene.bodies)?;
+    let observer = graph
+        .nodes()
+        .iter()
+        .position(|node| node.name == query.observer)
+        .ok_or_else(|| format!("Cuerpo observador desconocido: \"{}\"", query.observer))?;
+    if query.step <= 0.0 || query.end <= query.start {
+        return Err(format!(
+            "Intervalo de búsqueda inválido: de {} a {} con paso {}",
+            query.start, query.end, query.step
+        ));
+    }
+
+    let count = graph.nodes().len();
+    let pairs: Vec<(usize, usize)> = (0..count)
+        .filter(|&i| i != observer)
+        .flat_map(|i| (i + 1..count).filter(move |&j| j != observer).map(move |j| (i, j)))
+        .collect();
+
+    // Un evento en curso por par de cuerpos; los bordes se afinan al terminar cada paso
+    // para no alterar las posiciones mientras se recorren los pares
+    let mut active: Vec<Option<Event>> = vec![None; pairs.len()];
+    let mut events = Vec::new();
+    let steps = ((query.end - query.start) / query.step).ceil() as usize;
+    let mut previous_time = query.start;
+    for step in 0..=steps {
+        let time = (query.start + step as f32 * query.step).min(query.end);
+        graph.update(time);
+
+        let mut started = Vec::new();
+        let mut finished = Vec::new();
+        for (slot, &(i, j)) in active.iter_mut().zip(&pairs) {
+            match (overlap(&graph, observer, i, j), slot.as_mut()) {
+                (Some(overlap), Some(event)) => {
+                    if overlap.coverage > event.coverage {
+                        event.coverage = overlap.coverage;
+                        event.peak = time;
+                    }
+                }
+                (Some(overlap), None) => {
+                    let kind = if scene.bodies[overlap.back].material.kind == MaterialKind::Star {
+                        EventKind::Eclipse
+                    } else if overlap.front_larger {
+                        EventKind::Occultation
+                    } else {
+                        EventKind::Transit
+                    };
+                    *slot = Some(Event {
+                        kind,
+                        front: overlap.front,
+                        back: overlap.back,
+                        start: time,
+                        peak: time,
+                        end: query.end,
+                        coverage: overlap.coverage,
+                    });
+                    if step > 0 {
+                        started.push((i, j));
+                    }
+                }
+                (None, Some(_)) => finished.push((i, j)),
+                (None, None) => {}
+            }
+        }
+
+        for pair in started {
+            let index = pairs.iter().position(|&other| other == pair).unwrap_or_default();
+            let start = refine(&mut graph, observer, pair, previous_time, time);
+            if let Some(event) = &mut active[index] {
+                event.start = start;
+            }
+        }
+        for pair in finished {
+            let index = pairs.iter().position(|&other| other == pair).unwrap_or_default();
+            if let Some(mut event) = active[index].take() {
+                event.end = refine(&mut graph, observer, pair, previous_time, time);
+                events.push(event);
+            }
+        }
+        previous_time = time;
+    }
+
+    // Los eventos que siguen en curso terminan con el intervalo
+    events.extend(active.into_iter().flatten());
+    events.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(Prediction { observer, events })
+}
+
+impl Prediction {
+    pub fn print(&self, scene: &Scene, query: &EclipseQuery) {
+        let name = |index: usize| scene.bodies[index].name.as_str();
+        println!(
+            "Eventos vistos desde {} entre t = {:.1} s y t = {:.1} s",
+            name(self.observer),
+            query.start,
+            query.end
+        );
+        if self.events.is_empty() {
+            println!("Ninguno");
+            return;
+        }
+        println!(
+            "{:>3}  {:<11} {:<10} {:<10} {:>9} {:>9} {:>9} {:>9}",
+            "#", "Tipo", "Delante", "Detrás", "Inicio", "Máximo", "Fin", "Cubierto"
+        );
+        for (number, event) in self.events.iter().enumerate() {
+            println!(
+                "{:>3}  {:<11} {:<10} {:<10} {:>9.2} {:>9.2} {:>9.2} {:>8.0}%",
+                number + 1,
+                event.kind.label(),
+                name(event.front),
+                name(event.back),
+                event.start,
+                event.peak,
+                event.end,
+                event.coverage * 100.0
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sol, planeta y luna en el mismo plano: la luna eclipsa al Sol una vez por vuelta
+    const SCENE: &str = r#"
+        [[bodies]]
+        name = "Sol"
+        scale = 1.0
+        [bodies.material]
+        kind = "star"
+
+        [[bodies]]
+        name = "Planeta"
+        scale = 0.5
+        orbital_radius = 10.0
+        orbital_speed = 0.1
+        [bodies.material]
+        kind = "rocky"
+
+        [[bodies]]
+        name = "Luna"
+        parent = "Planeta"
+        scale = 0.3
+        orbital_radius = 2.0
+        orbital_speed = 2.0
+        [bodies.material]
+        kind = "rocky"
+    "#;
+
+    #[test]
+    fn overlap_area_limits() {
+        use std::f32::consts::PI;
+        assert_eq!(overlap_area(1.0, 1.0, 2.5), 0.0);
+        assert!((overlap_area(1.0, 0.5, 0.2) - PI * 0.25).abs() < 1e-5);
+        assert!((overlap_area(1.0, 1.0, 1e-3) - PI).abs() < 1e-2);
+    }
+
+    #[test]
+    fn moon_eclipses_the_sun() {
+        let scene: Scene = toml::from_str(SCENE).unwrap();
+        let query = EclipseQuery {
+            observer: "Planeta".to_string(),
+            end: 30.0,
+            ..EclipseQuery::default()
+        };
+        let prediction = predict(&scene, &query).unwrap();
+        let eclipse = prediction
+            .events
+            .iter()
+            .find(|event| event.kind == EventKind::Eclipse)
+            .expect("debería haber un eclipse");
+        assert_eq!((eclipse.front, eclipse.back), (2, 0));
+        assert!(eclipse.coverage > 0.99);
+        assert!(eclipse.start < eclipse.peak && eclipse.peak < eclipse.end);
+    }
+
+    #[test]
+    fn unknown_observer() {
+        let scene: Scene = toml::from_str(SCENE).unwrap();
+        let query = EclipseQuery {
+            observer: "Plutón".to_string(),
+            ..EclipseQuery::default()
+        };
+        assert!(predict(&scene, &query).is_err());
+    }
+}
@@ -0,0 +1,438 @@
+// Aterrizaje de la nave: choque con las superficies de los cuerpos, asentamiento sobre el
+// tren de aterrizaje alineada con la normal del suelo y despegue. Las posiciones de contacto
+// se guardan en el marco de cada cuerpo, así la nave posada acompaña su órbita y su giro.
+
+use cgmath::{InnerSpace, Matrix3, Matrix4, Quaternion, SquareMatrix, Vector3};
+
+use crate::material::{MaterialKind, MaterialParams};
+use crate::scene::BodyParams;
+use crate::scene_graph::SceneNode;
+use crate::terrain;
+
+// Altura del centro de la nave sobre el suelo cuando se apoya, en tamaños de la nave
+const GEAR_HEIGHT: f32 = 0.5;
+
+// Altura a la que el despegue deja a la nave, en tamaños de la nave
+const TAKEOFF_HEIGHT: f32 = 3.0;
+
+// Segundos que tardan el asentamiento sobre el tren y el despegue
+const SETTLE_TIME: f32 = 0.6;
+const TAKEOFF_TIME: f32 = 1.2;
+
+// Velocidad de contacto (unidades de la escena por segundo) por encima de la cual el aterrizaje es brusco
+pub const SAFE_TOUCHDOWN_SPEED: f32 = 0.5;
+
+// Paso de los controles manuales: lejos de todo es el de siempre y cerca del suelo se acorta
+const MAX_STEP: f32 = 0.2;
+const MIN_STEP: f32 = 0.002;
+
+// Por qué un cuerpo no admite aterrizajes; None si tiene superficie sólida
+pub fn landing_rejection(kind: MaterialKind) -> Option<&'static str> {
+    match kind {
+        MaterialKind::Rocky | MaterialKind::Ocean => None,
+        MaterialKind::GasGiant | MaterialKind::IceGiant => Some("no tiene superficie sólida"),
+        MaterialKind::Star => Some("es una estrella"),
+    }
+}
+
+// Superficie de un cuerpo a los efectos del choque, en su propio marco (radio 1)
+struct Surface {
+    material: MaterialParams,
+    relief: Option<f32>, // Relieve del terreno; None = esfera lisa
+}
+
+impl Surface {
+    // Radio de la superficie en la dirección unitaria `direction`
+    fn radius(&self, direction: Vector3<f32>) -> f32 {
+        self.relief
+            .map_or(1.0, |relief| terrain::surface_radius(&self.material, relief, direction))
+    }
+
+    // Normal del suelo en `direction`, con la pendiente del relieve si lo hay
+    fn normal(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        if self.relief.is_none() {
+            return direction;
+        }
+        const STEP: f32 = 1e-3;
+        let helper = if direction.y.abs() < 0.9 { Vector3::unit_y() } else { Vector3::unit_x() };
+        let u = direction.cross(helper).normalize();
+        let v = u.cross(direction);
+        let point = |offset: Vector3<f32>| {
+            let sample = (direction + offset).normalize();
+            sample * self.radius(sample)
+        };
+        let center = point(Vector3::new(0.0, 0.0, 0.0));
+        // v × u apunta hacia afuera porque u × v = -direction
+        (point(v * STEP) - center).cross(point(u * STEP) - center).normalize()
+    }
+}
+
+struct LandingBody {
+    name: String,
+    rejection: Option<&'static str>,
+    surface: Surface,
+}
+
+// Lo que pasó este frame, para avisarle al usuario
+pub enum LandingEvent {
+    Touchdown { body: String, speed: f32 },
+    Rejected { body: String, reason: &'static str },
+    Liftoff { body: String },
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Flight {
+    Flying,
+    // Apoyada: punto de contacto, normal y rumbo en el marco del cuerpo; `settle` va de 0 a 1
+    Landed {
+        body: usize,
+        contact: Vector3<f32>,
+        normal: Vector3<f32>,
+        forward: Vector3<f32>,
+        start: Quaternion<f32>, // Orientación al tocar el suelo
+        settle: f32,
+    },
+    TakingOff {
+        body: usize,
+        contact: Vector3<f32>,
+        normal: Vector3<f32>,
+        forward: Vector3<f32>,
+        progress: f32,
+    },
+}
+
+pub struct Lander {
+    bodies: Vec<LandingBody>, // En el mismo orden que los nodos del grafo de escena
+    flight: Flight,
+    previous: Option<(Vector3<f32>, Vec<Matrix4<f32>>)>, // Nave y cuerpos en el frame anterior
+    rejected_by: Option<usize>, // Evita repetir el aviso mientras la nave sigue contra el cuerpo
+}
+
+impl Lander {
+    pub fn new(bodies: &[BodyParams]) -> Self {
+        let bodies = bodies
+            .iter()
+            .map(|body| {
+                let rejection = landing_rejection(body.material.kind);
+                LandingBody {
+                    name: body.name.clone(),
+                    rejection,
+                    surface: Surface {
+                        material: body.material.clone(),
+                        relief: body.terrain.as_ref().filter(|_| rejection.is_none()).map(|params| params.relief.max(0.0)),
+                    },
+                }
+            })
+            .collect();
+        Self {
+            bodies,
+            flight: Flight::Flying,
+            previous: None,
+            rejected_by: None,
+        }
+    }
+
+    pub fn is_landed(&self) -> bool {
+        !matches!(self.flight, Flight::Flying)
+    }
+
+    // Empieza el despegue si la nave ya terminó de asentarse
+    pub fn take_off(&mut self) -> bool {
+        if let Flight::Landed { body, contact, normal, forward, settle, .. } = self.flight {
+            if settle >= 1.0 {
+                self.flight = Flight::TakingOff { body, contact, normal, forward, progress: 0.0 };
+                return true;
+            }
+        }
+        false
+    }
+
+    // El warp saca a la nave del suelo de golpe y su salto no cuenta como velocidad
+    pub fn cancel(&mut self) {
+        self.flight = Flight::Flying;
+        self.previous = None;
+        self.rejected_by = None;
+    }
+
+    // Paso de los controles manuales según la distancia al suelo más cercano
+    pub fn control_step(&self, nodes: &[SceneNode], position: Vector3<f32>) -> f32 {
+        let altitude = nodes
+            .iter()
+            .map(|node| (position - node.position).magnitude() - node.scale)
+            .fold(f32::INFINITY, f32::min);
+        (altitude * 0.25).clamp(MIN_STEP, MAX_STEP)
+    }
+
+    // Avanza un frame: mueve la nave si está posada o despegando, detecta los choques y
+    // devuelve su orientación. `flight_orientation` es la que dan los controles en vuelo y
+    // `size` el tamaño de la nave en la escena.
+    pub fn update(
+        &mut self,
+        nodes: &[SceneNode],
+        position: &mut Vector3<f32>,
+        flight_orientation: Quaternion<f32>,
+        size: f32,
+        dt: f32,
+    ) -> (Quaternion<f32>, Option<LandingEvent>) {
+        let mut event = None;
+        let orientation = match self.flight {
+            Flight::Flying => {
+                event = self.detect_contact(nodes, position, flight_orientation, size, dt);
+                match self.flight {
+                    Flight::Landed { start, .. } => start,
+                    _ => flight_orientation,
+                }
+            }
+            Flight::Landed { body, contact, normal, forward, start, settle } => {
+                let settle = (settle + dt / SETTLE_TIME).min(1.0);
+                self.flight = Flight::Landed { body, contact, normal, forward, start, settle };
+
+                // El tren se comprime un poco al apoyarse y vuelve a su altura
+                let height = GEAR_HEIGHT * (1.0 - 0.2 * (settle * std::f32::consts::PI).sin());
+                let (point, surface) = world_frame(&nodes[body].model, contact, normal, forward);
+                *position = point + surface * Vector3::unit_y() * height * size;
+                start.slerp(surface, smooth(settle))
+            }
+            Flight::TakingOff { body, contact, normal, forward, progress } => {
+                let progress = (progress + dt / TAKEOFF_TIME).min(1.0);
+                let height = GEAR_HEIGHT + (TAKEOFF_HEIGHT - GEAR_HEIGHT) * smooth(progress);
+                let (point, surface) = world_frame(&nodes[body].model, contact, normal, forward);
+                *position = point + surface * Vector3::unit_y() * height * size;
+                if progress >= 1.0 {
+                    self.flight = Flight::Flying;
+                    event = Some(LandingEvent::Liftoff { body: self.bodies[body].name.clone() });
+                } else {
+                    self.flight = Flight::TakingOff { body, contact, normal, forward, progress };
+                }
+                surface.slerp(flight_orientation, smooth(progress))
+            }
+        };
+
+        self.previous = Some((*position, nodes.iter().map(|node| node.model).collect()));
+        (orientation, event)
+    }
+
+    // Busca un cuerpo cuya superficie (más el tren de aterrizaje) toque la nave
+    fn detect_contact(
+        &mut self,
+        nodes: &[SceneNode],
+        position: &mut Vector3<f32>,
+        flight_orientation: Quaternion<f32>,
+        size: f32,
+        dt: f32,
+    ) -> Option<LandingEvent> {
+        for (index, (node, body)) in nodes.iter().zip(&self.bodies).enumerate() {
+            // Descarte rápido con la esfera que encierra el relieve
+            let reach = node.scale * (1.0 + body.surface.relief.unwrap_or(0.0) * 2.0) + GEAR_HEIGHT * size;
+            let clearance = GEAR_HEIGHT * size / node.scale;
+            let contact = if (*position - node.position).magnitude() > reach {
+                None
+            } else {
+                ground_contact(node, &body.surface, *position, clearance)
+            };
+            let Some(GroundContact { inverse, local, direction, ground }) = contact else {
+                if self.rejected_by == Some(index) {
+                    self.rejected_by = None;
+                }
+                continue;
+            };
+
+            if let Some(reason) = body.rejection {
+                // Se la empuja afuera: no hay dónde apoyarse
+                *position = (node.model * (direction * (ground + clearance)).extend(1.0)).truncate();
+                if self.rejected_by.replace(index).is_none() {
+                    return Some(LandingEvent::Rejected { body: body.name.clone(), reason });
+                }
+                return None;
+            }
+
+            // Velocidad respecto del suelo: desplazamiento en el marco del cuerpo, que gira y orbita
+            // (sin frame anterior no hay desplazamiento; sin su inversa no se puede medir y se espera)
+            let speed = match &self.previous {
+                Some((previous, models)) => {
+                    let Some(before_inverse) = models.get(index).and_then(|model| model.invert()) else {
+                        continue;
+                    };
+                    let before = (before_inverse * previous.extend(1.0)).truncate();
+                    (local - before).magnitude() * node.scale / dt
+                }
+                None => 0.0,
+            };
+
+            // Rumbo: el frente de la nave proyectado sobre el plano del suelo
+            let normal = body.surface.normal(direction);
+            let heading = (inverse * (flight_orientation * -Vector3::unit_z()).extend(0.0)).truncate();
+            let tangent = heading - normal * heading.dot(normal);
+            let forward = if tangent.magnitude2() > 1e-8 {
+                tangent.normalize()
+            } else {
+                normal.cross(Vector3::unit_x()).normalize()
+            };
+
+            self.flight = Flight::Landed {
+                body: index,
+                contact: direction * ground,
+                normal,
+                forward,
+                start: flight_orientation,
+                settle: 0.0,
+            };
+            let (point, surface) = world_frame(&node.model, direction * ground, normal, forward);
+            *position = point + surface * Vector3::unit_y() * GEAR_HEIGHT * size;
+            return Some(LandingEvent::Touchdown { body: body.name.clone(), speed });
+        }
+        None
+    }
+}
+
+struct GroundContact {
+    inverse: Matrix4<f32>, // Del mundo al marco del cuerpo
+    local: Vector3<f32>,   // Posición de la nave en el marco del cuerpo
+    direction: Vector3<f32>,
+    ground: f32, // Radio del suelo en `direction`
+}
+
+// Contacto de la nave con el suelo de un cuerpo, si lo toca. Un modelo sin inversa o la nave en el centro exacto no dan una dirección: se ignora el cuerpo
+fn ground_contact(
+    node: &SceneNode,
+    surface: &Surface,
+    position: Vector3<f32>,
+    clearance: f32,
+) -> Option<GroundContact> {
+    let inverse = node.model.invert()?;
+    let local = (inverse * position.extend(1.0)).truncate();
+    if local.magnitude2() < 1e-12 {
+        return None;
+    }
+    let direction = local.normalize();
+    let ground = surface.radius(direction);
+    (local.magnitude() < ground + clearance).then_some(GroundContact { inverse, local, direction, ground })
+}
+
+// Punto de contacto en el mundo y orientación de la nave apoyada: arriba según la normal
+// del suelo y el frente según el rumbo guardado
+fn world_frame(
+    model: &Matrix4<f32>,
+    contact: Vector3<f32>,
+    normal: Vector3<f32>,
+    forward: Vector3<f32>,
+) -> (Vector3<f32>, Quaternion<f32>) {
+    let point = (model * contact.extend(1.0)).truncate();
+    let up = (model * normal.extend(0.0)).truncate().normalize();
+    let forward = (model * forward.extend(0.0)).truncate();
+    (point, surface_orientation(up, forward))
+}
+
+// Orientación con el eje y de la nave sobre `up` y su frente (-z) lo más cerca posible de `forward`
+fn surface_orientation(up: Vector3<f32>, forward: Vector3<f32>) -> Quaternion<f32> {
+    let forward = (forward - up * forward.dot(up)).normalize();
+    let right = forward.cross(up);
+    Quaternion::from(Matrix3::from_cols(right, up, -forward))
+}
+
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene_graph::SceneGraph;
+
+    fn scene(kind: &str) -> (Lander, SceneGraph) {
+        let body: BodyParams = toml::from_str(&format!(
+            "name = \"Roca\"\nscale = 2.0\n[material]\nkind = \"{}\"",
+            kind
+        ))
+        .unwrap();
+        let mut graph = SceneGraph::new(std::slice::from_ref(&body)).unwrap();
+        graph.update(0.0);
+        (Lander::new(&[body]), graph)
+    }
+
+    #[test]
+    fn orientation_follows_the_ground() {
+        let up = Vector3::new(1.0, 1.0, 0.0).normalize();
+        let rotation = surface_orientation(up, Vector3::new(0.0, 0.0, -1.0));
+        assert!((rotation * Vector3::unit_y() - up).magnitude() < 1e-5);
+        assert!((rotation * -Vector3::unit_z() - Vector3::new(0.0, 0.0, -1.0)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn lands_on_rock_and_takes_off() {
+        let (mut lander, graph) = scene("rocky");
+        let level = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        let mut position = Vector3::new(0.0, 2.2, 0.0);
+        assert!(lander.update(graph.nodes(), &mut position, level, 0.1, 0.016).1.is_none());
+
+        // Bajando 0.004 por frame se apoya a 0.25 unidades por segundo
+        let mut touchdown = None;
+        for _ in 0..100 {
+            position.y -= 0.004;
+            if let (_, Some(LandingEvent::Touchdown { speed, .. })) =
+                lander.update(graph.nodes(), &mut position, level, 0.1, 0.016)
+            {
+                touchdown = Some(speed);
+                break;
+            }
+        }
+        let speed = touchdown.expect("la nave debía tocar el suelo");
+        assert!((speed - 0.25).abs() < 0.01);
+        assert!((position.y - (2.0 + GEAR_HEIGHT * 0.1)).abs() < 1e-4);
+
+        // Mientras se asienta no puede despegar; luego sube hasta TAKEOFF_HEIGHT
+        assert!(!lander.take_off());
+        for _ in 0..60 {
+            lander.update(graph.nodes(), &mut position, level, 0.1, 0.016);
+        }
+        assert!(lander.take_off());
+        let mut lifted = false;
+        for _ in 0..100 {
+            if let (_, Some(LandingEvent::Liftoff { .. })) = lander.update(graph.nodes(), &mut position, level, 0.1, 0.016) {
+                lifted = true;
+                break;
+            }
+        }
+        assert!(lifted && !lander.is_landed());
+        assert!((position.y - (2.0 + TAKEOFF_HEIGHT * 0.1)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gas_giants_and_stars_push_the_ship_out() {
+        for kind in ["gas_giant", "star"] {
+            let (mut lander, graph) = scene(kind);
+            let mut position = Vector3::new(0.0, 1.5, 0.0);
+            let level = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+            let (_, event) = lander.update(graph.nodes(), &mut position, level, 0.1, 0.016);
+            assert!(matches!(event, Some(LandingEvent::Rejected { .. })));
+            assert!(!lander.is_landed());
+            assert!(position.magnitude() >= 2.0);
+            // El aviso no se repite mientras sigue contra el cuerpo
+            position.y -= 0.01;
+            assert!(lander.update(graph.nodes(), &mut position, level, 0.1, 0.016).1.is_none());
+        }
+    }
+
+    #[test]
+    fn degenerate_bodies_do_not_hide_the_others() {
+        // Un cuerpo de escala 0 no tiene inversa; la nave igual debe apoyarse en la roca
+        let bodies: Vec<BodyParams> = ["name = \"Punto\"\nscale = 0.0", "name = \"Roca\"\nscale = 2.0"]
+            .iter()
+            .map(|source| toml::from_str(&format!("{}\n[material]\nkind = \"rocky\"", source)).unwrap())
+            .collect();
+        let mut graph = SceneGraph::new(&bodies).unwrap();
+        graph.update(0.0);
+        let mut lander = Lander::new(&bodies);
+        let level = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+        let mut position = Vector3::new(0.0, 3.0, 0.0);
+        let (_, event) = lander.update(graph.nodes(), &mut position, level, 5.0, 0.016);
+        assert!(matches!(event, Some(LandingEvent::Touchdown { ref body, .. }) if body == "Roca"));
+
+        // En el centro exacto de un cuerpo no hay dirección al suelo: se lo ignora sin NaN
+        let (mut lander, graph) = scene("rocky");
+        let mut position = Vector3::new(0.0, 0.0, 0.0);
+        let (orientation, event) = lander.update(graph.nodes(), &mut position, level, 0.1, 0.016);
+        assert!(event.is_none() && !lander.is_landed());
+        assert!(position.magnitude() == 0.0 && orientation.s.is_finite());
+    }
+}
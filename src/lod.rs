@@ -0,0 +1,203 @@
+// Niveles de detalle de las esferas: icosferas cada vez más subdivididas, compartidas por
+// todos los cuerpos, y elección del nivel según el tamaño del cuerpo en pantalla. La
+// histéresis evita que un cuerpo en el límite entre dos niveles salte de uno a otro.
+
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+// El nivel `n` es una icosfera con `n + 1` subdivisiones (80 a 81 920 triángulos)
+pub const LOD_LEVELS: usize = 6;
+
+// Largo en pantalla al que se apunta para las aristas de los triángulos
+const TARGET_EDGE_PIXELS: f32 = 8.0;
+
+// Margen, en niveles, antes de cambiar al nivel siguiente o al anterior
+const HYSTERESIS: f32 = 0.3;
+
+// Ángulo que abarca una arista del icosaedro visto desde el centro
+const ICOSAHEDRON_EDGE: f32 = 1.107_148_7;
+
+// Icosfera de radio 1: cada subdivisión parte cada triángulo en cuatro y proyecta los
+// vértices nuevos sobre la esfera. Los triángulos quedan en sentido antihorario vistos desde afuera.
+pub fn generate_icosphere(subdivisions: u32) -> (Vec<[f32; 3]>, Vec<u16>) {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let mut vertices: Vec<[f32; 3]> = [
+        [-1.0, t, 0.0],
+        [1.0, t, 0.0],
+        [-1.0, -t, 0.0],
+        [1.0, -t, 0.0],
+        [0.0, -1.0, t],
+        [0.0, 1.0, t],
+        [0.0, -1.0, -t],
+        [0.0, 1.0, -t],
+        [t, 0.0, -1.0],
+        [t, 0.0, 1.0],
+        [-t, 0.0, -1.0],
+        [-t, 0.0, 1.0],
+    ]
+    .iter()
+    .map(|&vertex| normalize(vertex))
+    .collect();
+
+    let mut triangles: Vec<[u16; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Cada arista se parte una sola vez aunque la compartan dos triángulos
+        let mut midpoints: HashMap<(u16, u16), u16> = HashMap::new();
+        let mut midpoint = |a: u16, b: u16, vertices: &mut Vec<[f32; 3]>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let (va, vb) = (vertices[a as usize], vertices[b as usize]);
+                vertices.push(normalize([va[0] + vb[0], va[1] + vb[1], va[2] + vb[2]]));
+                (vertices.len() - 1) as u16
+            })
+        };
+
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = midpoint(a, b, &mut vertices);
+                let bc = midpoint(b, c, &mut vertices);
+                let ca = midpoint(c, a, &mut vertices);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    (vertices, triangles.concat())
+}
+
+fn normalize([x, y, z]: [f32; 3]) -> [f32; 3] {
+    let length = (x * x + y * y + z * z).sqrt();
+    [x / length, y / length, z / length]
+}
+
+// Radio en píxeles de una esfera de radio `radius` a `distance` de la cámara
+pub fn projected_radius(radius: f32, distance: f32, fov_y: f32, viewport_height: f32) -> f32 {
+    if distance <= radius {
+        return f32::INFINITY; // Cámara dentro de la esfera
+    }
+    let angular_radius = (radius / distance).asin();
+    angular_radius.tan() / (fov_y * 0.5).tan() * viewport_height * 0.5
+}
+
+// Nivel continuo con el que las aristas miden `TARGET_EDGE_PIXELS`: cada nivel parte las
+// aristas a la mitad, así que hace falta uno más cada vez que el tamaño en pantalla se duplica
+fn ideal_level(projected_radius: f32) -> f32 {
+    (projected_radius * ICOSAHEDRON_EDGE / TARGET_EDGE_PIXELS).max(1e-6).log2() - 1.0
+}
+
+// Nivel para este frame: el actual se conserva mientras el ideal no se aleje más de la histéresis
+pub fn select_level(current: usize, projected_radius: f32) -> usize {
+    let ideal = ideal_level(projected_radius);
+    if ideal > current as f32 + HYSTERESIS || ideal < current as f32 - 1.0 - HYSTERESIS {
+        ideal.ceil().clamp(0.0, (LOD_LEVELS - 1) as f32) as usize
+    } else {
+        current
+    }
+}
+
+pub struct SphereMesh {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
+impl SphereMesh {
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
+
+// Una malla por nivel, compartida por los cuerpos, sus nubes y sus atmósferas
+pub struct SphereMeshes {
+    levels: Vec<SphereMesh>,
+}
+
+impl SphereMeshes {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let levels = (0..LOD_LEVELS)
+            .map(|level| {
+                let (vertices, indices) = generate_icosphere(level as u32 + 1);
+                let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("Sphere LOD {} Vertex Buffer", level)),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+                let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("Sphere LOD {} Index Buffer", level)),
+                    contents: bytemuck::cast_slice(&indices),
+                    usage: wgpu::BufferUsages::INDEX,
+                });
+                SphereMesh {
+                    vertex_buffer,
+                    index_buffer,
+                    num_indices: indices.len() as u32,
+                }
+            })
+            .collect();
+        Self { levels }
+    }
+
+    pub fn level(&self, level: usize) -> &SphereMesh {
+        &self.levels[level.min(LOD_LEVELS - 1)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icosphere_is_closed_and_faces_outward() {
+        for subdivisions in 0..=LOD_LEVELS as u32 {
+            let (vertices, indices) = generate_icosphere(subdivisions);
+            let faces = 20 * 4usize.pow(subdivisions);
+            assert_eq!(indices.len(), faces * 3);
+            // Euler: V - E + F = 2 con E = 3F/2
+            assert_eq!(vertices.len(), 2 + faces / 2);
+            for triangle in indices.chunks(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| cgmath::Vector3::from(vertices[triangle[i] as usize]));
+                let normal = (b - a).cross(c - a);
+                assert!(cgmath::InnerSpace::dot(normal, a + b + c) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn level_grows_with_size_and_holds_near_thresholds() {
+        assert_eq!(select_level(LOD_LEVELS - 1, 1.0), 0);
+        assert_eq!(select_level(0, 1e5), LOD_LEVELS - 1);
+
+        // Justo por encima del límite del nivel 2 se conserva el 2; un poco más allá pasa al 3
+        let radius_for = |level: f32| TARGET_EDGE_PIXELS * (level + 1.0).exp2() / ICOSAHEDRON_EDGE;
+        assert_eq!(select_level(2, radius_for(2.1)), 2);
+        assert_eq!(select_level(2, radius_for(2.5)), 3);
+        // Y al volver apenas por debajo no regresa al 2
+        assert_eq!(select_level(3, radius_for(1.9)), 3);
+        assert_eq!(select_level(3, radius_for(1.5)), 2);
+    }
+}
@@ -5,8 +5,64 @@ use winit::{
     window::WindowBuilder,
 };
 
-use rand::Rng;
+mod atmosphere;
+mod belts;
+mod clouds;
+mod comets;
+mod corona;
+mod culling;
+mod eclipses;
+mod landing;
+mod lines;
+mod lod;
+mod material;
+mod nebula;
+mod noise;
+mod orbits;
+mod postprocess;
+mod preprocessor;
+mod render_graph;
+mod rings;
+mod scene;
+mod scene_graph;
+mod settings;
 mod shaders;
+mod shadows;
+mod skybox;
+mod stars;
+mod terrain;
+
+use atmosphere::{Atmosphere, AtmospherePipeline};
+use belts::AsteroidBelts;
+use clouds::{CloudPipeline, Clouds};
+use comets::Comets;
+use corona::{Corona, CoronaPipeline};
+use culling::{BoundingSphere, CullingStats, Frustum};
+use landing::{Lander, LandingEvent};
+use lines::{LineBatch, LineVertex};
+use lod::SphereMeshes;
+use material::{MaterialKind, MaterialUniforms};
+use orbits::{Orbit, Trail};
+use postprocess::{PostProcess, BLOOM_LEVELS, HDR_FORMAT};
+use render_graph::{draw_fn, ColorTarget, DepthTarget, FrameTargets, PassNode, PassStage, RenderGraph};
+use rings::{Ring, RingPipeline};
+use scene::Scene;
+use scene_graph::SceneGraph;
+use settings::Settings;
+use shadows::Shadows;
+use skybox::{CubemapImage, Skybox};
+use stars::StarField;
+use terrain::{Terrain, TerrainPipeline};
+
+// Estelas: una muestra cada TRAIL_INTERVAL frames, hasta TRAIL_LENGTH muestras
+const TRAIL_INTERVAL: u32 = 4;
+const TRAIL_LENGTH: usize = 120;
+
+// Escala de la malla de la nave en el mundo
+const SPACESHIP_SCALE: f32 = 0.2;
+
+// Formato del index buffer de la nave: load_obj_model devuelve índices u32
+const SPACESHIP_INDEX_FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint32;
 
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -17,16 +73,8 @@ struct Uniforms {
     time: f32, // Agregamos tiempo dinámico para animaciones
     orbital_radius: f32,  // Añadimos el radio orbital
     orbital_speed: f32,   // Añadimos la velocidad orbital
-}
-
-
-
-// Primero agregamos una nueva estructura para manejar las estrellas
-const STAR_COUNT: usize = 2000000;
-
-struct Star {
-    position: [f32; 3],
-    brightness: f32,
+    _padding: f32,        // Alinea el struct a 16 bytes como en WGSL
+    camera_position: [f32; 4], // Posición de la cámara en el mundo, para los reflejos especulares
 }
 
 
@@ -53,30 +101,13 @@ fn load_obj_model(file_path: &str) -> (Vec<[f32; 3]>, Vec<u32>) {
         );
 
         // Extraer índices
-        indices.extend(mesh.indices.iter().map(|&index| index as u32));
+        indices.extend(mesh.indices.iter().copied());
     }
 
     (vertices, indices)
 }
 
 
-impl Star {
-    fn new(rng: &mut impl rand::Rng) -> Self {
-        let theta = rng.gen_range(0.0..std::f32::consts::PI * 2.0); // Ángulo azimutal (0 a 360°).
-        let phi = rng.gen_range(-std::f32::consts::PI / 2.0..std::f32::consts::PI / 2.0); // Ángulo polar (-90° a 90°).
-        let radius = rng.gen_range(5.0..20.0); // Radio de la esfera.
-
-        Self {
-            position: [
-                radius * phi.cos() * theta.cos(), // Coordenada X.
-                radius * phi.cos() * theta.sin(), // Coordenada Y.
-                radius * phi.sin(),               // Coordenada Z.
-            ],
-            brightness: rng.gen_range(0.5..1.0),
-        }
-    }
-}
-
 struct Spaceship {
     pipeline: wgpu::RenderPipeline,
     uniform_buffer: wgpu::Buffer,
@@ -85,15 +116,20 @@ struct Spaceship {
     index_buffer: wgpu::Buffer,
     num_indices: u32,
     uniforms: Uniforms,
+    shadow_bind_group: wgpu::BindGroup, // Uniforms de la nave para el shadow map
+    radius: f32,                        // Radio que encierra la malla, sin escalar
+    visible: bool,                      // Dentro del frustum de la cámara en este frame
 }
 
 impl Spaceship {
     fn new_from_obj(
         device: &wgpu::Device,
-        config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
         file_path: &str,
         scale: f32,
         color: [f32; 4],
+        shadows: &Shadows,
     ) -> Self {
         // Load OBJ model
         let (vertices, indices) = load_obj_model(file_path);
@@ -115,20 +151,15 @@ impl Spaceship {
             ],
         });
 
-        let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Spaceship Vertex Shader"),
-            source: wgpu::ShaderSource::Wgsl(shaders::VERTEX_SHADER.into()),
-        });
-
-        let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Spaceship Fragment Shader"),
-            source: wgpu::ShaderSource::Wgsl(shaders::FRAGMENT_SHADER_8.into()),
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Spaceship Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("spaceship.wgsl", &[]).into()),
         });
 
         // Create pipeline layout with uniform bind group layout
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Spaceship Pipeline Layout"),
-            bind_group_layouts: &[&uniform_bind_group_layout],
+            bind_group_layouts: &[&uniform_bind_group_layout, &shadows.bind_group_layout],
             push_constant_ranges: &[],
         });
 
@@ -136,7 +167,7 @@ impl Spaceship {
             label: Some("Spaceship Pipeline"),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &vertex_shader,
+                module: &shader,
                 entry_point: "vs_main",
                 buffers: &[wgpu::VertexBufferLayout {
                     array_stride: std::mem::size_of::<[f32; 3]>() as u64,
@@ -145,10 +176,10 @@ impl Spaceship {
                 }],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &fragment_shader,
+                module: &shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
+                    format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
@@ -170,14 +201,14 @@ impl Spaceship {
                 bias: wgpu::DepthBiasState::default(),
             }),
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
         });
 
-        let uniforms = Uniforms::new([0.0, 0.0, 0.0], color, scale, 0.0, 0.0);
+        let uniforms = Uniforms::new(color, scale, 0.0, 0.0);
 
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Spaceship Uniform Buffer"),
@@ -208,6 +239,12 @@ impl Spaceship {
             usage: wgpu::BufferUsages::INDEX,
         });
 
+        let shadow_bind_group = shadows.caster_bind_group(device, &uniform_buffer);
+        let radius = vertices
+            .iter()
+            .map(|[x, y, z]| (x * x + y * y + z * z).sqrt())
+            .fold(0.0, f32::max);
+
         Spaceship {
             pipeline,
             uniform_buffer,
@@ -216,46 +253,66 @@ impl Spaceship {
             index_buffer,
             num_indices: indices.len() as u32,
             uniforms,
+            shadow_bind_group,
+            radius,
+            visible: true,
         }
     }
 }
 
 
 
-// Shaders de las estrellas
-// Actualización de los shaders de estrellas.
-const STAR_VERTEX_SHADER: &str = r#"
-@vertex
-fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
-    let view_proj: mat4x4<f32> = mat4x4<f32>(
-        vec4<f32>(1.0, 0.0, 0.0, 0.0),
-        vec4<f32>(0.0, 1.0, 0.0, 0.0),
-        vec4<f32>(0.0, 0.0, 1.0, 0.0),
-        vec4<f32>(0.0, 0.0, -10.0, 1.0), // Ajusta la distancia de la cámara.
-    );
-    return view_proj * vec4<f32>(position, 1.0);
+// Cámara fija que observa el sistema desde arriba
+fn camera_view() -> cgmath::Matrix4<f32> {
+    cgmath::Matrix4::look_at_rh(
+        cgmath::Point3::new(0.0, 5.0, 28.0),  // Ajustamos la cámara para mejor vista
+        cgmath::Point3::new(0.0, 0.0, 0.0),
+        cgmath::Vector3::unit_y(),
+    )
 }
-"#;
 
-const STAR_FRAGMENT_SHADER: &str = r#"
-@fragment
-fn main() -> @location(0) vec4<f32> {
-    return vec4(1.0, 1.0, 1.0, 1.0); // Color blanco brillante para las estrellas.
+// Cámara que sigue a un cuerpo, a una distancia proporcional a su tamaño
+fn follow_camera_view(target: cgmath::Vector3<f32>, scale: f32) -> cgmath::Matrix4<f32> {
+    let target = cgmath::Point3::new(target.x, target.y, target.z);
+    let offset = cgmath::Vector3::new(0.0, 1.5, 5.0) * scale.max(0.3);
+    cgmath::Matrix4::look_at_rh(target + offset, target, cgmath::Vector3::unit_y())
 }
-"#;
 
+// Vista desde la superficie de `observer` hacia `target`, para mirar un eclipse
+fn event_camera_view(observer: cgmath::Vector3<f32>, scale: f32, target: cgmath::Vector3<f32>) -> cgmath::Matrix4<f32> {
+    use cgmath::InnerSpace;
+    let direction = (target - observer).normalize();
+    let eye = observer + direction * scale * 1.1;
+    let up = if direction.y.abs() > 0.99 { cgmath::Vector3::unit_z() } else { cgmath::Vector3::unit_y() };
+    cgmath::Matrix4::look_at_rh(
+        cgmath::Point3::new(eye.x, eye.y, eye.z),
+        cgmath::Point3::new(target.x, target.y, target.z),
+        up,
+    )
+}
 
-impl Uniforms {
-    fn new(position: [f32; 3], color: [f32; 4], scale: f32, orbital_radius: f32, orbital_speed: f32) -> Self {
-        let view = cgmath::Matrix4::look_at_rh(
-            cgmath::Point3::new(0.0, 5.0, 28.0),  // Ajustamos la cámara para mejor vista
-            cgmath::Point3::new(0.0, 0.0, 0.0),
-            cgmath::Vector3::unit_y(),
-        );
+// Cámara detrás de la nave, girada con su rumbo; `size` es el tamaño de la nave en la escena
+fn ship_camera_view(position: cgmath::Vector3<f32>, yaw: f32, size: f32) -> cgmath::Matrix4<f32> {
+    let offset = cgmath::Matrix3::from_angle_y(cgmath::Rad(yaw)) * cgmath::Vector3::new(0.0, 1.0, 3.0) * size;
+    let target = cgmath::Point3::new(position.x, position.y, position.z);
+    cgmath::Matrix4::look_at_rh(target + offset, target, cgmath::Vector3::unit_y())
+}
 
-        let aspect_ratio = 800.0 / 600.0;
-        let proj = cgmath::perspective(cgmath::Deg(60.0), aspect_ratio, 0.1, 200.0); // Far plane mayor que 50.0
+// Campo de visión vertical de la cámara
+const CAMERA_FOV_Y: cgmath::Deg<f32> = cgmath::Deg(60.0);
 
+// Plano cercano por defecto; se acerca al volar sobre una superficie para no recortarla
+const CAMERA_NEAR: f32 = 0.1;
+const CAMERA_NEAR_MIN: f32 = 0.0005;
+
+fn camera_projection(aspect_ratio: f32, near: f32) -> cgmath::Matrix4<f32> {
+    cgmath::perspective(CAMERA_FOV_Y, aspect_ratio, near, 200.0) // Far plane mayor que 50.0
+}
+
+impl Uniforms {
+    fn new(color: [f32; 4], scale: f32, orbital_radius: f32, orbital_speed: f32) -> Self {
+        let view = camera_view();
+        let proj = camera_projection(800.0 / 600.0, CAMERA_NEAR);
 
         Self {
             view_proj: (proj * view).into(),
@@ -264,18 +321,34 @@ impl Uniforms {
             time: 0.0,
             orbital_radius,
             orbital_speed,
+            _padding: 0.0,
+            camera_position: [0.0, 5.0, 28.0, 1.0],
         }
     }
 }
 
+// Partes de cada cuerpo dentro del frustum de la cámara en el último frame
+#[derive(Clone, Copy, Default)]
+struct Visibility {
+    body: bool,
+    rings: bool,
+    atmosphere: bool,
+    clouds: bool,
+    corona: bool,
+}
+
 struct Sphere {
-    pipeline: wgpu::RenderPipeline,
     uniform_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
+    lod: usize, // Nivel de detalle elegido en el último frame
     uniforms: Uniforms, // Guardamos los uniforms localmente
+    rings: Option<Ring>,
+    corona: Option<Corona>,
+    atmosphere: Option<Atmosphere>,
+    clouds: Option<Clouds>,
+    terrain: Option<Terrain>, // Relieve en parches que reemplaza a la esfera de cerca
+    casts_shadow: bool, // Las estrellas iluminan, no tapan
+    visible: Visibility,
 }
 
 struct State {
@@ -284,108 +357,138 @@ struct State {
     surface: wgpu::Surface,
     config: wgpu::SurfaceConfiguration,
     depth_view: wgpu::TextureView,
-    spheres: Vec<Sphere>,
-    star_buffer: wgpu::Buffer,
-    num_stars: u32,
-    star_pipeline: wgpu::RenderPipeline,
+    sample_count: u32, // Muestras MSAA de la escena y de su profundidad
+    planet_pipeline: wgpu::RenderPipeline, // Compartido: cada esfera cambia solo su material
+    spheres: Vec<Sphere>, // Una esfera por nodo del grafo de escena, en el mismo orden
+    sphere_meshes: SphereMeshes, // Icosferas de cada nivel de detalle, compartidas
+    scene_graph: SceneGraph,
+    selected_node: usize,         // Destino elegido con Tab para el warp y la cámara
+    camera_target: Option<usize>, // Nodo que sigue la cámara; None = vista general
+    event_camera: Option<(usize, usize)>, // Observador y cuerpo tapado de un evento (--jump-to-event)
+    follow_ship: bool, // Cámara detrás de la nave (V)
+    camera_view: cgmath::Matrix4<f32>, // Vista y proyección del fotograma actual, calculadas una vez en update
+    camera_proj: cgmath::Matrix4<f32>,
+    axis_lines: LineBatch,
+    show_axes: bool,
+    orbit_lines: LineBatch, // Órbitas completas y estelas
+    show_orbits: bool,
+    trails: Vec<Trail>, // Una por nodo del grafo de escena
+    spaceship_trail: Trail,
+    trail_frame: u32,
+    ring_pipeline: RingPipeline, // Compartido por los anillos de todos los cuerpos
+    corona_pipeline: CoronaPipeline, // Compartido por las coronas de las estrellas
+    atmosphere_pipeline: AtmospherePipeline, // Compartido por las atmósferas de todos los cuerpos
+    cloud_pipeline: CloudPipeline, // Compartido por las capas de nubes
+    terrain_pipeline: TerrainPipeline, // Compartido por los terrenos de los cuerpos sólidos
+    belts: AsteroidBelts,
+    comets: Comets,
+    stars: StarField,
+    show_stars: bool,
+    skybox: Option<Skybox>,
+    post_process: PostProcess, // Textura HDR, bloom y tonemapping
+    shadows: Shadows, // Cuerpos y anillos que tapan el Sol y shadow map de la nave
+    sun_node: Option<usize>, // Estrella que ilumina la escena (la primera de la lista)
     spaceship: Spaceship, // Agrega este campo
     spaceship_position: cgmath::Vector3<f32>, // Posición de la nave
     spaceship_rotation: cgmath::Vector3<f32>,
+    spaceship_orientation: cgmath::Quaternion<f32>, // La de los controles o la del suelo si está posada
+    lander: Lander, // Choques con los cuerpos, aterrizaje y despegue
+    culling: CullingStats, // Dibujados y descartados por el frustum en el último frame
+    show_culling: bool,    // Cuentas del culling en el título de la ventana (I)
+    render_graph: RenderGraph<State>,
 }
 
 
 impl State {
 
-    fn render_stars(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Star Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Load, // Mantener contenido previo.
-                    store: true,
-                },
-            })],
-            depth_stencil_attachment: None,
+    fn create_depth_view(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, sample_count: u32) -> wgpu::TextureView {
+        let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Depth Texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
         });
-    
-        render_pass.set_pipeline(&self.star_pipeline);
-        render_pass.set_vertex_buffer(0, self.star_buffer.slice(..));
-        render_pass.draw(0..self.num_stars, 0..1); // Asegúrate de que `num_stars` sea correcto.
+        depth_texture.create_view(&wgpu::TextureViewDescriptor::default())
     }
-    
 
-    fn generate_star_pipeline(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::RenderPipeline {
-        let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Star Vertex Shader"),
-            source: wgpu::ShaderSource::Wgsl(STAR_VERTEX_SHADER.into()),
-        });
-    
-        let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Star Fragment Shader"),
-            source: wgpu::ShaderSource::Wgsl(STAR_FRAGMENT_SHADER.into()),
-        });
-    
-        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Star Pipeline"),
-            layout: None,
-            vertex: wgpu::VertexState {
-                module: &vertex_shader,
-                entry_point: "vs_main", // Asegúrate de que coincida con el shader
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<[f32; 3]>() as u64,
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    attributes: &wgpu::vertex_attr_array![0 => Float32x3],
-                }],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &fragment_shader,
-                entry_point: "main", // Asegúrate de que coincida con el shader
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::PointList, // Renderizar como puntos
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },            
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-        })
+    fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        if size.width == 0 || size.height == 0 {
+            return; // Ventana minimizada
+        }
+        self.config.width = size.width;
+        self.config.height = size.height;
+        self.surface.configure(&self.device, &self.config);
+        self.depth_view = Self::create_depth_view(&self.device, &self.config, self.sample_count);
+        self.post_process.resize(&self.device, &self.config);
     }
-    
 
+    // Matrices de vista y proyección con la relación de aspecto actual de la ventana
+    fn camera_matrices(&self) -> (cgmath::Matrix4<f32>, cgmath::Matrix4<f32>) {
+        let aspect_ratio = self.config.width as f32 / self.config.height as f32;
+        let nodes = self.scene_graph.nodes();
+        let view = match (self.event_camera, self.camera_target) {
+            (Some((observer, target)), _) => {
+                event_camera_view(nodes[observer].position, nodes[observer].scale, nodes[target].position)
+            }
+            _ if self.follow_ship => ship_camera_view(
+                self.spaceship_position,
+                self.spaceship_rotation.y,
+                self.spaceship.radius * SPACESHIP_SCALE,
+            ),
+            (None, Some(index)) => {
+                let node = &self.scene_graph.nodes()[index];
+                follow_camera_view(node.position, node.scale)
+            }
+            (None, None) => camera_view(),
+        };
+
+        // Cerca de una superficie el plano cercano baja con la altura de la cámara
+        let eye = cgmath::SquareMatrix::invert(&view).map_or(cgmath::Vector3::new(0.0, 0.0, 0.0), |inverse| inverse.w.truncate());
+        let altitude = nodes
+            .iter()
+            .map(|node| cgmath::InnerSpace::magnitude(node.position - eye) - node.scale)
+            .fold(f32::INFINITY, f32::min);
+        let near = (altitude * 0.5).clamp(CAMERA_NEAR_MIN, CAMERA_NEAR);
+        (view, camera_projection(aspect_ratio, near))
+    }
 
+    // Muestras MSAA pedidas si el adaptador las admite para el color HDR y la profundidad;
+    // si no, las 4 que exige WebGPU
+    fn supported_sample_count(adapter: &wgpu::Adapter, requested: u32) -> u32 {
+        let supported = |count| {
+            [HDR_FORMAT, wgpu::TextureFormat::Depth32Float]
+                .iter()
+                .all(|&format| adapter.get_texture_format_features(format).flags.sample_count_supported(count))
+        };
+        if requested == 1 || requested == 4 {
+            return requested;
+        }
+        if adapter.features().contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) && supported(requested) {
+            return requested;
+        }
+        eprintln!("El adaptador no admite MSAA de {} muestras; se usan 4", requested);
+        4
+    }
 
-    fn generate_stars(device: &wgpu::Device) -> (wgpu::Buffer, u32) {
-        let mut rng = rand::thread_rng();
-        let stars: Vec<[f32; 3]> = (0..STAR_COUNT)
-            .map(|_| Star::new(&mut rng).position)
-            .collect();
-    
-        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Star Buffer"),
-            contents: bytemuck::cast_slice(&stars),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-    
-        (buffer, STAR_COUNT as u32)
+    fn control_step(&self) -> f32 {
+        self.lander.control_step(self.scene_graph.nodes(), self.spaceship_position)
     }
-    
-    async fn new(window: &winit::window::Window) -> Self {
+
+    // Punto junto al cuerpo donde aparece la nave al hacer warp
+    fn warp_position(&self, index: usize) -> cgmath::Vector3<f32> {
+        let node = &self.scene_graph.nodes()[index];
+        node.position + cgmath::Vector3::new(0.0, 0.0, node.scale * 2.0 + 0.5)
+    }
+
+    async fn new(window: &winit::window::Window, settings: &Settings, scene: &Scene) -> Self {
         
         
         let size = window.inner_size();
@@ -396,10 +499,18 @@ impl State {
             .await
             .unwrap();
     
+        // MSAA: 4 muestras están garantizadas; 2 y 8 dependen del adaptador
+        let sample_count = Self::supported_sample_count(&adapter, settings.msaa_samples);
+        let features = if sample_count == 4 || sample_count == 1 {
+            wgpu::Features::empty()
+        } else {
+            wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+        };
+
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: Some("Device Descriptor"),
-                features: wgpu::Features::empty(),
+                features,
                 limits: wgpu::Limits::default(),
             }, None)
             .await
@@ -417,23 +528,12 @@ impl State {
         surface.configure(&device, &config);
     
         // Crear textura de profundidad
-        let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Depth Texture"),
-            size: wgpu::Extent3d {
-                width: config.width,
-                height: config.height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-            view_formats: &[],
-        });
-        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = Self::create_depth_view(&device, &config, sample_count);
     
-        // Layout de uniformes
+        // Sombras: compartidas por los pipelines que reciben la luz del Sol (grupo 1)
+        let shadows = Shadows::new(&device);
+
+        // Layout de uniformes: transformaciones y material del cuerpo
         let uniform_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Uniform Bind Group Layout"),
             entries: &[
@@ -447,207 +547,217 @@ impl State {
                     },
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         });
     
-        // Shaders de esferas
-        let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Vertex Shader"),
-            source: wgpu::ShaderSource::Wgsl(shaders::VERTEX_SHADER.into()),
+        // Shader de material compartido por todas las esferas
+        let planet_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Planet Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("planet.wgsl", &[]).into()),
         });
-    
-        let shaders = vec![
-            shaders::FRAGMENT_SHADER_SUN,
-            shaders::FRAGMENT_SHADER_2,
-            shaders::FRAGMENT_SHADER_3,
-            shaders::FRAGMENT_SHADER_4,
-            shaders::FRAGMENT_SHADER_5,
-            shaders::FRAGMENT_SHADER_6,
-            shaders::FRAGMENT_SHADER_7,
-            shaders::FRAGMENT_SHADER_8,
-        ];
-    
-        let positions = vec![
-            [-15.0, 0.0, 0.0], // Sol
-            [-8.5, 0.0, 0.0],  // Mercurio
-            [-6.0, 0.0, 0.0],  // Venus
-            [-3.0, 0.0, 0.0],  // Tierra
-            [0.0, 0.0, 0.0],   // Marte
-            [3.0, 0.0, 0.0],   // Júpiter
-            [7.0, 0.0, 0.0],   // Saturno
-            [10.0, 0.0, 0.0],  // Urano
-        ];
-    
-        let colors = [
-            [1.0, 0.9, 0.0, 1.0], // Sol
-            [0.5, 0.5, 1.0, 1.0], // Mercurio
-            [0.8, 0.5, 0.2, 1.0], // Venus
-            [0.0, 0.5, 1.0, 1.0], // Tierra
-            [1.0, 0.3, 0.3, 1.0], // Marte
-            [0.3, 1.0, 0.3, 1.0], // Júpiter
-            [0.5, 0.2, 0.7, 1.0], // Saturno
-            [0.7, 0.7, 0.7, 1.0], // Urano
-        ];
-    
-        let scales = [
-            4.5, // Sol
-            0.6, // Mercurio
-            0.9, // Venus
-            1.05, // Tierra
-            0.75, // Marte
-            1.5, // Júpiter
-            1.2, // Saturno
-            1.05, // Urano
-        ];
-    
-        let orbital_speeds = [
-            0.0,  // Sol
-            1.6,  // Mercurio
-            1.2,  // Venus
-            1.0,  // Tierra
-            0.8,  // Marte
-            0.4,  // Júpiter
-            0.3,  // Saturno
-            0.2,  // Urano
-        ];
-    
-        let orbital_radii = [
-            0.0,  // Sol
-            7.0,  // Mercurio
-            9.0,  // Venus
-            11.0, // Tierra
-            13.0, // Marte
-            15.0, // Júpiter
-            17.0, // Saturno
-            19.0, // Urano
-        ];
+
+        let planet_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Planet Pipeline"),
+            layout: Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Pipeline Layout"),
+                bind_group_layouts: &[&uniform_bind_group_layout, &shadows.bind_group_layout],
+                push_constant_ranges: &[],
+            })),
+            vertex: wgpu::VertexState {
+                module: &planet_shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<[f32; 3]>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &planet_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent::REPLACE,
+                        alpha: wgpu::BlendComponent::REPLACE,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
         let spaceship = Spaceship::new_from_obj(
             &device,
-            &config,
+            HDR_FORMAT,
+            sample_count,
             "assets/model3d.obj",
             0.5,                     // Escala
             [1.0, 1.0, 1.0, 1.0],    // Color
+            &shadows,
         );
-        
-        
-    
+
+        let ring_pipeline = RingPipeline::new(&device, HDR_FORMAT, sample_count, &shadows.bind_group_layout);
+        let corona_pipeline = CoronaPipeline::new(&device, HDR_FORMAT, sample_count);
+        let atmosphere_pipeline = AtmospherePipeline::new(&device, HDR_FORMAT, sample_count, &shadows.bind_group_layout);
+        let cloud_pipeline = CloudPipeline::new(&device, HDR_FORMAT, sample_count, &shadows.bind_group_layout);
+        let terrain_pipeline = TerrainPipeline::new(
+            &device,
+            HDR_FORMAT,
+            sample_count,
+            &uniform_bind_group_layout,
+            &shadows.bind_group_layout,
+        );
+        let scene_graph = SceneGraph::new(&scene.bodies).unwrap_or_else(|error| panic!("{}", error));
+
+        // Los cuerpos, sus materiales y sus anillos vienen de la escena
         let mut spheres = Vec::new();
     
-        for (i, position) in positions.iter().enumerate() {
-            if i >= shaders.len() {
-                break;
-            }
-    
-            let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("Fragment Shader"),
-                source: wgpu::ShaderSource::Wgsl(shaders[i].into()),
-            });
-    
-            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some(&format!("Pipeline {}", i)),
-                layout: Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Pipeline Layout"),
-                    bind_group_layouts: &[&uniform_bind_group_layout],
-                    push_constant_ranges: &[],
-                })),
-                vertex: wgpu::VertexState {
-                    module: &vertex_shader,
-                    entry_point: "vs_main",
-                    buffers: &[wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<[f32; 3]>() as u64,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &wgpu::vertex_attr_array![0 => Float32x3],
-                    }],
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &fragment_shader,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: config.format,
-                        blend: Some(wgpu::BlendState {
-                            color: wgpu::BlendComponent::REPLACE,
-                            alpha: wgpu::BlendComponent::REPLACE,
-                        }),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Less,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-            });
-    
+        for body in &scene.bodies {
+            let [r, g, b] = body.color;
             let uniforms = Uniforms::new(
-                *position,
-                colors[i],
-                scales[i],
-                orbital_radii[i],
-                orbital_speeds[i],
+                [r, g, b, 1.0],
+                body.scale,
+                body.orbital_radius,
+                body.orbital_speed,
             );
     
             let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Uniform Buffer"),
+                label: Some(&format!("{} Uniform Buffer", body.name)),
                 contents: bytemuck::cast_slice(&[uniforms]),
                 usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             });
+
+            let material_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{} Material Buffer", body.name)),
+                contents: bytemuck::cast_slice(&[MaterialUniforms::new(
+                    &body.material,
+                    body.clouds.as_ref(),
+                    body.rotation_period,
+                )]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
     
             let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
                 label: Some("Uniform Bind Group"),
                 layout: &uniform_bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: uniform_buffer.as_entire_binding(),
-                }],
-            });
-    
-            let (vertices, indices) = generate_sphere(60, 60);
-    
-            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Vertex Buffer"),
-                contents: bytemuck::cast_slice(&vertices),
-                usage: wgpu::BufferUsages::VERTEX,
-            });
-    
-            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(&indices),
-                usage: wgpu::BufferUsages::INDEX,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: material_buffer.as_entire_binding(),
+                    },
+                ],
             });
     
+            let rings = body
+                .rings
+                .as_ref()
+                .map(|params| Ring::new(&device, &ring_pipeline, body, params));
+            let corona = body
+                .corona
+                .as_ref()
+                .map(|params| Corona::new(&device, &corona_pipeline, body, params));
+            let atmosphere = body
+                .atmosphere
+                .as_ref()
+                .map(|params| Atmosphere::new(&device, &atmosphere_pipeline, body, params));
+            let clouds = body
+                .clouds
+                .as_ref()
+                .map(|params| Clouds::new(&device, &cloud_pipeline, body, params));
+            let terrain = body.terrain.as_ref().and_then(|params| Terrain::new(body, params));
+
             spheres.push(Sphere {
-                pipeline,
                 uniform_buffer,
                 bind_group,
-                vertex_buffer,
-                index_buffer,
-                num_indices: indices.len() as u32,
+                lod: 0,
                 uniforms,
+                rings,
+                corona,
+                atmosphere,
+                clouds,
+                terrain,
+                casts_shadow: body.material.kind != MaterialKind::Star,
+                visible: Visibility::default(),
             });
         }
     
-        // Generar pipeline de estrellas
-        let star_pipeline = Self::generate_star_pipeline(&device, &config);
-    
-        // Generar buffer de estrellas
-        let (star_buffer, num_stars) = Self::generate_stars(&device);
+        // Icosferas de todos los niveles de detalle; cada cuerpo elige la suya en cada frame
+        let sphere_meshes = SphereMeshes::new(&device);
+
+        // Cinturones de asteroides: las rocas se generan una vez y orbitan en la GPU
+        let belts = AsteroidBelts::new(&device, HDR_FORMAT, sample_count, &shadows.bind_group_layout, &scene.belts);
+
+        // Cometas: las colas se orientan en cada frame según la posición del Sol
+        let comets = Comets::new(&device, HDR_FORMAT, sample_count, &scene.comets);
+
+        // Campo de estrellas (generado en la GPU salvo que se pida el respaldo en CPU)
+        let stars = StarField::new(&device, HDR_FORMAT, sample_count, settings, &scene.background);
+
+        // Skybox: imagen indicada con --skybox o, si no hay, la nebulosa procedural horneada
+        let skybox = if settings.background.has_skybox() {
+            let max_size = device.limits().max_texture_dimension_2d;
+            let loaded = settings.skybox_path.as_ref().and_then(|path| match CubemapImage::load(path, max_size) {
+                Ok(image) => Some(image.create_texture(&device, &queue)),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    None
+                }
+            });
+            let cubemap = loaded.unwrap_or_else(|| nebula::bake_nebula(&device, &queue, &scene.background));
+            Some(Skybox::new(&device, HDR_FORMAT, sample_count, cubemap))
+        } else {
+            None
+        };
+        let show_stars = settings.background.has_stars();
+
+        // La escena se dibuja en HDR; el bloom y el tonemapping producen la imagen final
+        let post_process = PostProcess::new(&device, &config, settings, sample_count);
+
+        // Un segmento (dos vértices) por cuerpo
+        let axis_lines = LineBatch::new(&device, HDR_FORMAT, sample_count, "Axis Lines", scene.bodies.len() * 2);
+
+        // La elipse y la estela de cada cuerpo, la elipse de cada cometa y la estela de la nave
+        let trail_vertices = Trail::path_vertex_count(TRAIL_LENGTH);
+        let orbit_capacity = scene.bodies.len() * (Orbit::path_vertex_count() + trail_vertices)
+            + scene.comets.len() * Orbit::path_vertex_count()
+            + trail_vertices;
+        let orbit_lines = LineBatch::new(&device, HDR_FORMAT, sample_count, "Orbit Lines", orbit_capacity);
 
     
         Self {
@@ -656,167 +766,545 @@ impl State {
             surface,
             config,
             depth_view,
+            sample_count,
+            planet_pipeline,
             spheres,
-            star_buffer,
-            num_stars,
-            star_pipeline,
+            sphere_meshes,
+            scene_graph,
+            selected_node: 0,
+            camera_target: None,
+            event_camera: None,
+            follow_ship: false,
+            camera_view: cgmath::SquareMatrix::identity(),
+            camera_proj: cgmath::SquareMatrix::identity(),
+            axis_lines,
+            show_axes: false,
+            orbit_lines,
+            show_orbits: false,
+            trails: scene.bodies.iter().map(|_| Trail::new(TRAIL_LENGTH)).collect(),
+            spaceship_trail: Trail::new(TRAIL_LENGTH),
+            trail_frame: 0,
+            ring_pipeline,
+            corona_pipeline,
+            atmosphere_pipeline,
+            cloud_pipeline,
+            terrain_pipeline,
+            belts,
+            comets,
+            stars,
+            show_stars,
+            skybox,
+            post_process,
             spaceship,
             spaceship_position: cgmath::Vector3::new(0.0, 0.0, 0.0), // Posición inicial
             spaceship_rotation: cgmath::Vector3::new(0.0, 0.0, 0.0), // Sin rotación inicial
-
+            spaceship_orientation: cgmath::Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            lander: Lander::new(&scene.bodies),
+            culling: CullingStats::default(),
+            show_culling: false,
+            shadows,
+            sun_node: scene.bodies.iter().position(|body| body.material.kind == MaterialKind::Star),
+            render_graph: Self::build_render_graph(settings.fxaa),
         }
         
     }
     
 
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
-        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Command Encoder"),
+    // Declaración de los pases del frame; el grafo se encarga del orden y de limpiar los destinos
+    fn build_render_graph(fxaa: bool) -> RenderGraph<State> {
+        let mut graph = RenderGraph::new(wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
         });
 
-        self.render_stars(&mut encoder, &view);
-
-        // Render pass para las estrellas
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Star Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None, // Las estrellas no necesitan profundidad
-            });
-    
-            render_pass.set_pipeline(&self.star_pipeline);
-            render_pass.set_vertex_buffer(0, self.star_buffer.slice(..));
-            render_pass.draw(0..self.num_stars, 0..1);
+        graph.add_pass(PassNode {
+            name: "Shadow Map Pass",
+            stage: PassStage::Shadow,
+            color: None,
+            depth: Some(DepthTarget::Shadow),
+            draw: draw_fn(Self::draw_shadow_casters),
+        });
+        graph.add_pass(PassNode {
+            name: "Skybox Render Pass",
+            stage: PassStage::Background,
+            color: Some(ColorTarget::Hdr),
+            depth: None,
+            draw: draw_fn(Self::draw_skybox),
+        });
+        graph.add_pass(PassNode {
+            name: "Star Render Pass",
+            stage: PassStage::Background,
+            color: Some(ColorTarget::Hdr),
+            depth: None, // Las estrellas no necesitan profundidad
+            draw: draw_fn(Self::draw_stars),
+        });
+        graph.add_pass(PassNode {
+            name: "Planet Render Pass",
+            stage: PassStage::Opaque,
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene),
+            draw: draw_fn(Self::draw_spheres),
+        });
+        graph.add_pass(PassNode {
+            name: "Belt Render Pass",
+            stage: PassStage::Opaque,
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene),
+            draw: draw_fn(Self::draw_belts),
+        });
+        graph.add_pass(PassNode {
+            name: "Cloud Render Pass",
+            stage: PassStage::Transparent,
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene),
+            draw: draw_fn(Self::draw_clouds),
+        });
+        graph.add_pass(PassNode {
+            name: "Atmosphere Render Pass",
+            stage: PassStage::Transparent,
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene),
+            draw: draw_fn(Self::draw_atmospheres),
+        });
+        graph.add_pass(PassNode {
+            name: "Corona Render Pass",
+            stage: PassStage::Transparent,
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene),
+            draw: draw_fn(Self::draw_coronas),
+        });
+        graph.add_pass(PassNode {
+            name: "Ring Render Pass",
+            stage: PassStage::Transparent,
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene),
+            draw: draw_fn(Self::draw_rings),
+        });
+        graph.add_pass(PassNode {
+            name: "Comet Render Pass",
+            stage: PassStage::Transparent,
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene),
+            draw: draw_fn(Self::draw_comets),
+        });
+        graph.add_pass(PassNode {
+            name: "Orbit Render Pass",
+            stage: PassStage::Overlay,
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene),
+            draw: draw_fn(Self::draw_orbits),
+        });
+        graph.add_pass(PassNode {
+            name: "Axis Render Pass",
+            stage: PassStage::Overlay,
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene), // Cada cuerpo oculta la parte del eje que lo atraviesa
+            draw: draw_fn(Self::draw_axes),
+        });
+        graph.add_pass(PassNode {
+            name: "Spaceship Render Pass",
+            stage: PassStage::Transparent, // La nave se desvanece durante el warping
+            color: Some(ColorTarget::Hdr),
+            depth: Some(DepthTarget::Scene),
+            draw: draw_fn(Self::draw_spaceship),
+        });
 
+        // Bloom: brillos a media resolución, reducción nivel a nivel y ampliación sumando hacia arriba
+        graph.add_pass(PassNode {
+            name: "Bloom Prefilter Pass",
+            stage: PassStage::PostProcess,
+            color: Some(ColorTarget::Bloom(0)),
+            depth: None,
+            draw: draw_fn(|state: &State, render_pass| state.post_process.draw_prefilter(render_pass)),
+        });
+        for level in 1..BLOOM_LEVELS {
+            graph.add_pass(PassNode {
+                name: "Bloom Downsample Pass",
+                stage: PassStage::PostProcess,
+                color: Some(ColorTarget::Bloom(level)),
+                depth: None,
+                draw: draw_fn(move |state: &State, render_pass| state.post_process.draw_downsample(render_pass, level)),
+            });
         }
-    
-        // Render pass para los objetos 3D
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load, // Mantiene el contenido previo (las estrellas)
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0), // Limpia la profundidad para los objetos 3D
-                        store: true,
-                    }),
-                    stencil_ops: None,
-                }),
+        for level in (1..BLOOM_LEVELS).rev() {
+            graph.add_pass(PassNode {
+                name: "Bloom Upsample Pass",
+                stage: PassStage::PostProcess,
+                color: Some(ColorTarget::Bloom(level - 1)),
+                depth: None,
+                draw: draw_fn(move |state: &State, render_pass| state.post_process.draw_upsample(render_pass, level)),
             });
-    
-            // Renderizado de las esferas en orden
-            let mut render_order: Vec<(usize, f32)> = self.spheres
-                .iter()
-                .enumerate()
-                .map(|(i, sphere)| {
-                    // Extraer la posición Z de la matriz model
-                    let translation_z = sphere.uniforms.model[3][2];
-                    (i, translation_z)
-                })
-                .collect();
-    
-            // Ordenar de atrás hacia adelante (z más negativo primero)
-            render_order.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-    
-            for (index, _) in render_order {
-                let sphere = &self.spheres[index];
-                render_pass.set_pipeline(&sphere.pipeline);
+        }
+        graph.add_pass(PassNode {
+            name: "Tonemap Pass",
+            stage: PassStage::PostProcess,
+            color: Some(if fxaa { ColorTarget::Ldr } else { ColorTarget::Surface }),
+            depth: None,
+            draw: draw_fn(|state: &State, render_pass| state.post_process.draw_tonemap(render_pass)),
+        });
+        if fxaa {
+            graph.add_pass(PassNode {
+                name: "FXAA Pass",
+                stage: PassStage::PostProcess,
+                color: Some(ColorTarget::Surface),
+                depth: None,
+                draw: draw_fn(|state: &State, render_pass| state.post_process.draw_fxaa(render_pass)),
+            });
+        }
+
+        graph
+    }
+
+    fn draw_skybox<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if let Some(skybox) = &self.skybox {
+            skybox.draw(render_pass);
+        }
+    }
+
+    fn draw_stars<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.show_stars {
+            self.stars.draw(render_pass);
+        }
+    }
+
+    fn draw_spheres<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        // Renderizado de las esferas en orden según su profundidad en la vista
+        let view = self.camera_view;
+        let mut render_order: Vec<(usize, f32)> = self.scene_graph
+            .nodes()
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| self.spheres[i].visible.body)
+            .map(|(i, node)| (i, (view * node.position.extend(1.0)).z))
+            .collect();
+
+        // Ordenar de atrás hacia adelante (z más negativo primero)
+        render_order.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        render_pass.set_pipeline(&self.planet_pipeline);
+        render_pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        for &(index, _) in &render_order {
+            let sphere = &self.spheres[index];
+            if sphere.terrain.as_ref().is_some_and(Terrain::is_active) {
+                continue;
+            }
+            render_pass.set_bind_group(0, &sphere.bind_group, &[]);
+            self.sphere_meshes.level(sphere.lod).draw(render_pass);
+        }
+
+        // Cuerpos cercanos a la cámara: sus parches de terreno en lugar de la esfera
+        render_pass.set_pipeline(&self.terrain_pipeline.pipeline);
+        render_pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        for (index, _) in render_order {
+            let sphere = &self.spheres[index];
+            if let Some(terrain) = sphere.terrain.as_ref().filter(|terrain| terrain.is_active()) {
                 render_pass.set_bind_group(0, &sphere.bind_group, &[]);
-                render_pass.set_vertex_buffer(0, sphere.vertex_buffer.slice(..));
-                render_pass.set_index_buffer(sphere.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                render_pass.draw_indexed(0..sphere.num_indices, 0, 0..1);
+                terrain.draw(render_pass, &self.terrain_pipeline);
             }
-            render_pass.set_pipeline(&self.spaceship.pipeline);
-            render_pass.set_bind_group(0, &self.spaceship.bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.spaceship.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.spaceship.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.spaceship.num_indices, 0, 0..1);
+        }
+    }
 
+    fn draw_belts<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        self.belts.draw(render_pass);
+    }
+
+    fn draw_clouds<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        // Translúcidas: de atrás hacia adelante según la profundidad en la vista
+        let view = self.camera_view;
+        let mut layers: Vec<(&Clouds, usize)> = self
+            .spheres
+            .iter()
+            .filter(|sphere| sphere.visible.clouds)
+            .filter_map(|sphere| Some((sphere.clouds.as_ref()?, sphere.lod)))
+            .collect();
+        layers.sort_by(|(a, _), (b, _)| {
+            let depth_a = (view * a.center.extend(1.0)).z;
+            let depth_b = (view * b.center.extend(1.0)).z;
+            depth_a.partial_cmp(&depth_b).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        render_pass.set_pipeline(&self.cloud_pipeline.pipeline);
+        render_pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        for (clouds, lod) in layers {
+            clouds.draw(render_pass, self.sphere_meshes.level(lod));
         }
-        
-    
-        self.queue.submit(std::iter::once(encoder.finish())); // Enviar comandos a la GPU
-        output.present(); // Presentar el frame actual en la ventana
-    
-        Ok(())
     }
-    
-}
 
-fn generate_sphere(stacks: usize, slices: usize) -> (Vec<[f32; 3]>, Vec<u16>) {
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
+    fn draw_atmospheres<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        // Translúcidas: de atrás hacia adelante según la profundidad en la vista
+        let view = self.camera_view;
+        let mut atmospheres: Vec<(&Atmosphere, usize)> = self
+            .spheres
+            .iter()
+            .filter(|sphere| sphere.visible.atmosphere)
+            .filter_map(|sphere| Some((sphere.atmosphere.as_ref()?, sphere.lod)))
+            .collect();
+        atmospheres.sort_by(|(a, _), (b, _)| {
+            let depth_a = (view * a.center.extend(1.0)).z;
+            let depth_b = (view * b.center.extend(1.0)).z;
+            depth_a.partial_cmp(&depth_b).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        render_pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        for (atmosphere, lod) in atmospheres {
+            let pipeline = if atmosphere.contains_camera() {
+                &self.atmosphere_pipeline.inside
+            } else {
+                &self.atmosphere_pipeline.pipeline
+            };
+            render_pass.set_pipeline(pipeline);
+            atmosphere.draw(render_pass, self.sphere_meshes.level(lod));
+        }
+    }
+
+    fn draw_coronas<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.corona_pipeline.pipeline);
+        let visible = self.spheres.iter().filter(|sphere| sphere.visible.corona);
+        for corona in visible.filter_map(|sphere| sphere.corona.as_ref()) {
+            corona.draw(render_pass);
+        }
+    }
 
-    for i in 0..=stacks {
-        let stack_angle = std::f32::consts::PI * i as f32 / stacks as f32 - std::f32::consts::PI / 2.0;
-        let xy = stack_angle.cos();
-        let z = stack_angle.sin();
+    fn draw_comets<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        self.comets.draw(render_pass);
+    }
 
-        for j in 0..=slices {
-            let slice_angle = 2.0 * std::f32::consts::PI * j as f32 / slices as f32;
-            let x = xy * slice_angle.cos();
-            let y = xy * slice_angle.sin();
-            vertices.push([x, y, z]);
+    fn draw_rings<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        // Translúcidos: de atrás hacia adelante según la profundidad en la vista
+        let view = self.camera_view;
+        let mut rings: Vec<&Ring> = self
+            .spheres
+            .iter()
+            .filter(|sphere| sphere.visible.rings)
+            .filter_map(|sphere| sphere.rings.as_ref())
+            .collect();
+        rings.sort_by(|a, b| {
+            let depth_a = (view * a.center.extend(1.0)).z;
+            let depth_b = (view * b.center.extend(1.0)).z;
+            depth_a.partial_cmp(&depth_b).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        render_pass.set_pipeline(&self.ring_pipeline.pipeline);
+        render_pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        for ring in rings {
+            ring.draw(render_pass);
         }
     }
 
-    for i in 0..stacks {
-        for j in 0..slices {
-            let first = i * (slices + 1) + j;
-            let second = first + slices + 1;
+    fn draw_axes<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.show_axes {
+            self.axis_lines.draw(render_pass);
+        }
+    }
 
-            indices.push(first as u16);
-            indices.push(second as u16);
-            indices.push(first as u16 + 1);
+    // Segmentos que atraviesan cada cuerpo de polo a polo, con su color identificativo
+    fn update_axes(&mut self, view_proj: [[f32; 4]; 4]) {
+        let vertices: Vec<LineVertex> = self
+            .scene_graph
+            .nodes()
+            .iter()
+            .zip(&self.spheres)
+            .flat_map(|(node, sphere)| {
+                let half_axis = node.axis * node.scale * 1.6;
+                [node.position + half_axis, node.position - half_axis].map(|point| LineVertex {
+                    position: point.into(),
+                    color: sphere.uniforms.color,
+                })
+            })
+            .collect();
+        self.axis_lines.update(&self.queue, view_proj, &vertices);
+    }
 
-            indices.push(second as u16);
-            indices.push(second as u16 + 1);
-            indices.push(first as u16 + 1);
+    fn draw_orbits<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.show_orbits {
+            self.orbit_lines.draw(render_pass);
         }
     }
 
-    (vertices, indices)
+    // Esferas y anillos que pueden tapar el Sol, y la caja del shadow map alrededor de la nave
+    fn update_shadows(&mut self) {
+        let nodes = self.scene_graph.nodes();
+        let (sun, sun_radius) = match self.sun_node {
+            Some(index) => (nodes[index].position, nodes[index].scale),
+            None => (cgmath::Vector3::new(0.0, 0.0, 0.0), 1.0),
+        };
+        let spheres: Vec<[f32; 4]> = nodes
+            .iter()
+            .zip(&self.spheres)
+            .filter(|(_, sphere)| sphere.casts_shadow)
+            .map(|(node, _)| node.position.extend(node.scale).into())
+            .collect();
+        let rings: Vec<_> = self.spheres.iter().filter_map(|sphere| sphere.rings.as_ref()).map(|ring| ring.occluder).collect();
+        self.shadows.set_bodies(sun, sun_radius, &spheres, &rings);
+
+        let radius = self.spaceship.radius * SPACESHIP_SCALE;
+        self.shadows.set_caster(self.spaceship_position, radius, self.spaceship.uniforms.color[3]);
+        self.shadows.update(&self.queue);
+    }
+
+    // Guarda una muestra de la posición de cada cuerpo y de la nave cada pocos frames
+    fn record_trails(&mut self) {
+        self.trail_frame = (self.trail_frame + 1) % TRAIL_INTERVAL;
+        if self.trail_frame != 0 {
+            return;
+        }
+        for (trail, node) in self.trails.iter_mut().zip(self.scene_graph.nodes()) {
+            trail.push(node.position);
+        }
+        self.spaceship_trail.push(self.spaceship_position);
+    }
+
+    // Elipses alrededor de la posición actual de cada padre y estelas que se desvanecen
+    fn update_orbits(&mut self, view_proj: [[f32; 4]; 4]) {
+        let nodes = self.scene_graph.nodes();
+        let mut vertices = Vec::new();
+        for ((node, sphere), trail) in nodes.iter().zip(&self.spheres).zip(&self.trails) {
+            if node.orbit.semi_major_axis <= 0.0 {
+                continue;
+            }
+            let [r, g, b, _] = sphere.uniforms.color;
+            let center = node.parent.map_or(cgmath::Vector3::new(0.0, 0.0, 0.0), |parent| nodes[parent].position);
+            node.orbit.append_path(center, [r, g, b, 0.35], &mut vertices);
+            trail.append_path([r, g, b, 0.9], &mut vertices);
+        }
+        self.comets.append_orbits(&mut vertices);
+        self.spaceship_trail.append_path([0.6, 0.8, 1.0, 0.9], &mut vertices);
+        self.orbit_lines.update(&self.queue, view_proj, &vertices);
+    }
+
+    // Mallas que proyectan sombra, vistas desde el Sol
+    fn draw_shadow_casters<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.shadows.caster_pipeline);
+        render_pass.set_bind_group(0, &self.spaceship.shadow_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.shadows.light_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.spaceship.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.spaceship.index_buffer.slice(..), SPACESHIP_INDEX_FORMAT);
+        render_pass.draw_indexed(0..self.spaceship.num_indices, 0, 0..1);
+    }
+
+    fn draw_spaceship<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if !self.spaceship.visible {
+            return;
+        }
+        render_pass.set_pipeline(&self.spaceship.pipeline);
+        render_pass.set_bind_group(0, &self.spaceship.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.spaceship.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.spaceship.index_buffer.slice(..), SPACESHIP_INDEX_FORMAT);
+        render_pass.draw_indexed(0..self.spaceship.num_indices, 0, 0..1);
+    }
+
+    // Frustum culling del frame: cada cuerpo con sus capas, los cinturones, los cometas y la
+    // nave se comparan contra la vista por su esfera envolvente. El shadow map de la nave no
+    // depende de esto: su sombra puede caer en pantalla aunque ella no esté.
+    fn cull(&mut self, frustum: &Frustum) {
+        let mut stats = CullingStats::default();
+        for (sphere, node) in self.spheres.iter_mut().zip(self.scene_graph.nodes()) {
+            let relief = sphere.terrain.as_ref().map_or(1.0, Terrain::max_radius);
+            let body = BoundingSphere::new(node.position, node.scale * relief);
+            sphere.visible = Visibility {
+                body: stats.bodies.record(frustum.intersects(&body)),
+                rings: sphere.rings.as_ref().is_some_and(|rings| stats.rings.record(frustum.intersects(&rings.bounds()))),
+                atmosphere: sphere
+                    .atmosphere
+                    .as_ref()
+                    .is_some_and(|atmosphere| stats.atmospheres.record(frustum.intersects(&atmosphere.bounds()))),
+                clouds: sphere.clouds.as_ref().is_some_and(|clouds| stats.clouds.record(frustum.intersects(&clouds.bounds()))),
+                corona: sphere.corona.as_ref().is_some_and(|corona| stats.coronas.record(frustum.intersects(&corona.bounds()))),
+            };
+        }
+        stats.belts = self.belts.cull(frustum);
+        stats.comets = self.comets.cull(frustum);
+        let ship = BoundingSphere::new(self.spaceship_position, self.spaceship.radius * SPACESHIP_SCALE);
+        self.spaceship.visible = stats.ship.record(frustum.intersects(&ship));
+        self.culling = stats;
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Command Encoder"),
+        });
+
+        let targets = FrameTargets {
+            surface: &view,
+            hdr: &self.post_process.hdr_view,
+            hdr_multisampled: self.post_process.hdr_multisampled_view.as_ref(),
+            ldr: &self.post_process.ldr_view,
+            bloom: &self.post_process.bloom_views,
+            depth: &self.depth_view,
+            shadow: &self.shadows.shadow_view,
+        };
+        self.render_graph.execute(self, &mut encoder, &targets);
+
+        self.queue.submit(std::iter::once(encoder.finish())); // Enviar comandos a la GPU
+        output.present(); // Presentar el frame actual en la ventana
+    
+        Ok(())
+    }
+    
 }
 
 
 
+
 fn main() {
     pollster::block_on(run());
 }
 
 async fn run() {
+    let settings = Settings::from_args();
+    let scene = Scene::load(&settings.scene_path).unwrap_or_else(|error| panic!("{}", error));
+
+    // Con --eclipses se imprime la predicción; sin --jump-to-event no se abre la ventana
+    let mut event = None;
+    if let Some(query) = &settings.eclipses {
+        let prediction = match eclipses::predict(&scene, query) {
+            Ok(prediction) => prediction,
+            Err(error) => {
+                eprintln!("{}", error);
+                return;
+            }
+        };
+        prediction.print(&scene, query);
+        let Some(number) = query.jump_to else {
+            return;
+        };
+        match prediction.events.get(number.wrapping_sub(1)) {
+            Some(found) => event = Some((prediction.observer, found.clone())),
+            None => {
+                eprintln!("No hay un evento número {} (se encontraron {})", number, prediction.events.len());
+                return;
+            }
+        }
+    }
+
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new().build(&event_loop).unwrap();
 
-    let mut state = State::new(&window).await;
+    let mut state = State::new(&window, &settings, &scene).await;
     let mut current_time: f32 = 0.0;
+    if let Some((observer, event)) = event {
+        current_time = event.start;
+        state.event_camera = Some((observer, event.back));
+        window.set_title(&format!(
+            "{}: {} delante de {} desde {}",
+            event.kind.label(),
+            scene.bodies[event.front].name,
+            scene.bodies[event.back].name,
+            scene.bodies[observer].name
+        ));
+    }
 
     // Variables para manejar el efecto de warping
     let mut is_warping = false; // Indica si está en medio de un warping
     let mut warp_time = 0.0; // Tiempo transcurrido en la animación de warping
-    let mut target_position = cgmath::Vector3::new(0.0, 0.0, 0.0); // Posición destino para el warping
+    let mut stats_frame: u32 = 0; // Cuenta hasta 30 frames para refrescar las cuentas del culling
+    let mut warp_target = 0; // Nodo de la escena al que se dirige el warping
 
     // Constantes para la animación
     const WARP_DURATION: f32 = 1.0; // Duración total del efecto (en segundos)
@@ -828,6 +1316,7 @@ async fn run() {
             Event::WindowEvent { event, .. } => {
                 match event {
                     WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                    WindowEvent::Resized(size) => state.resize(size),
                     
                     // Capturar teclas para movimiento manual y warping
                     WindowEvent::KeyboardInput {
@@ -838,64 +1327,98 @@ async fn run() {
                                 ..
                             },
                         ..
-                    } => {
+                    } if !is_warping => {
                         // Warping instantáneo
-                        if !is_warping {
-                            match key {
-                                VirtualKeyCode::Key1 => {
-                                    target_position = cgmath::Vector3::new(-15.0, 0.0, 0.0); // Sol
+                        match key {
+                            // Teclas 1-9: cuerpos principales (sin padre) en el orden de la escena
+                            VirtualKeyCode::Key1
+                            | VirtualKeyCode::Key2
+                            | VirtualKeyCode::Key3
+                            | VirtualKeyCode::Key4
+                            | VirtualKeyCode::Key5
+                            | VirtualKeyCode::Key6
+                            | VirtualKeyCode::Key7
+                            | VirtualKeyCode::Key8
+                            | VirtualKeyCode::Key9 => {
+                                let digit = key as usize - VirtualKeyCode::Key1 as usize;
+                                if let Some(&index) = state.scene_graph.roots().get(digit) {
+                                    state.selected_node = index;
+                                    warp_target = index;
                                     is_warping = true; // Inicia la animación
                                     warp_time = 0.0;
                                 }
-                                VirtualKeyCode::Key2 => {
-                                    target_position = cgmath::Vector3::new(-8.5, 0.0, 0.0); // Mercurio
-                                    is_warping = true;
-                                    warp_time = 0.0;
-                                }
-                                VirtualKeyCode::Key3 => {
-                                    target_position = cgmath::Vector3::new(-6.0, 0.0, 0.0); // Venus
-                                    is_warping = true;
-                                    warp_time = 0.0;
-                                }
-                                VirtualKeyCode::Key4 => {
-                                    target_position = cgmath::Vector3::new(-3.0, 0.0, 0.0); // Tierra
-                                    is_warping = true;
-                                    warp_time = 0.0;
-                                }
-                                VirtualKeyCode::Key5 => {
-                                    target_position = cgmath::Vector3::new(0.0, 0.0, 0.0); // Marte
-                                    is_warping = true;
-                                    warp_time = 0.0;
-                                }
-                                VirtualKeyCode::Key6 => {
-                                    target_position = cgmath::Vector3::new(3.0, 0.0, 0.0); // Júpiter
-                                    is_warping = true;
-                                    warp_time = 0.0;
-                                }
-                                VirtualKeyCode::Key7 => {
-                                    target_position = cgmath::Vector3::new(7.0, 0.0, 0.0); // Saturno
-                                    is_warping = true;
-                                    warp_time = 0.0;
-                                }
-                                VirtualKeyCode::Key8 => {
-                                    target_position = cgmath::Vector3::new(10.0, 0.0, 0.0); // Urano
-                                    is_warping = true;
-                                    warp_time = 0.0;
-                                }
+                            }
 
-                                // Movimiento manual
-                                VirtualKeyCode::W => state.spaceship_position.z -= 0.2, // Adelante
-                                VirtualKeyCode::S => state.spaceship_position.z += 0.2, // Atrás
-                                VirtualKeyCode::A => state.spaceship_position.x -= 0.2, // Izquierda
-                                VirtualKeyCode::D => state.spaceship_position.x += 0.2, // Derecha
-                                VirtualKeyCode::Space => state.spaceship_position.y += 0.2, // Subir
-                                VirtualKeyCode::LShift => state.spaceship_position.y -= 0.2, // Bajar
-                                VirtualKeyCode::Left => state.spaceship_rotation.y -= 0.05, // Rotar izquierda
-                                VirtualKeyCode::Right => state.spaceship_rotation.y += 0.05, // Rotar derecha
-                                VirtualKeyCode::Up => state.spaceship_rotation.x -= 0.05, // Rotar arriba
-                                VirtualKeyCode::Down => state.spaceship_rotation.x += 0.05, // Rotar abajo
-                                _ => {}
+                            // Tab elige cualquier cuerpo, incluidas las lunas; Enter viaja hasta él
+                            VirtualKeyCode::Tab => {
+                                state.selected_node = (state.selected_node + 1) % state.scene_graph.nodes().len();
+                                window.set_title(&format!("Destino: {}", state.scene_graph.nodes()[state.selected_node].name));
                             }
+                            VirtualKeyCode::Return => {
+                                warp_target = state.selected_node;
+                                is_warping = true;
+                                warp_time = 0.0;
+                            }
+
+                            // O muestra u oculta las órbitas y las estelas
+                            VirtualKeyCode::O => state.show_orbits = !state.show_orbits,
+
+                            // I muestra en el título cuántos objetos se dibujan y cuántos descarta el frustum
+                            VirtualKeyCode::I => state.show_culling = !state.show_culling,
+
+                            // X muestra u oculta los ejes de rotación
+                            VirtualKeyCode::X => state.show_axes = !state.show_axes,
+
+                            // B activa o desactiva el bloom; + y - ajustan la exposición
+                            VirtualKeyCode::B => state.post_process.bloom_enabled = !state.post_process.bloom_enabled,
+                            VirtualKeyCode::Equals | VirtualKeyCode::Plus | VirtualKeyCode::NumpadAdd => {
+                                state.post_process.exposure = (state.post_process.exposure * 1.25).min(16.0);
+                                window.set_title(&format!("Exposición: {:.2}", state.post_process.exposure));
+                            }
+                            VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => {
+                                state.post_process.exposure = (state.post_process.exposure / 1.25).max(0.05);
+                                window.set_title(&format!("Exposición: {:.2}", state.post_process.exposure));
+                            }
+
+                            // C alterna entre la vista general y seguir al destino elegido
+                            VirtualKeyCode::C => {
+                                // Primero se abandona la vista del eclipse, si la hay
+                                state.camera_target = match (state.event_camera.take(), state.camera_target) {
+                                    (Some(_), target) => target,
+                                    (None, Some(_)) => None,
+                                    (None, None) => Some(state.selected_node),
+                                };
+                                state.follow_ship = false;
+                            }
+
+                            // V pone la cámara detrás de la nave o la devuelve a la vista anterior
+                            VirtualKeyCode::V => {
+                                state.event_camera = None;
+                                state.follow_ship = !state.follow_ship;
+                            }
+
+                            // Posada, Espacio despega y el resto del movimiento espera a estar en el aire
+                            VirtualKeyCode::Space if state.lander.take_off() => window.set_title("Despegando"),
+                            VirtualKeyCode::W
+                            | VirtualKeyCode::S
+                            | VirtualKeyCode::A
+                            | VirtualKeyCode::D
+                            | VirtualKeyCode::Space
+                            | VirtualKeyCode::LShift
+                                if state.lander.is_landed() => {}
+
+                            // Movimiento manual; cerca de una superficie los pasos se acortan
+                            VirtualKeyCode::W => state.spaceship_position.z -= state.control_step(), // Adelante
+                            VirtualKeyCode::S => state.spaceship_position.z += state.control_step(), // Atrás
+                            VirtualKeyCode::A => state.spaceship_position.x -= state.control_step(), // Izquierda
+                            VirtualKeyCode::D => state.spaceship_position.x += state.control_step(), // Derecha
+                            VirtualKeyCode::Space => state.spaceship_position.y += state.control_step(), // Subir
+                            VirtualKeyCode::LShift => state.spaceship_position.y -= state.control_step(), // Bajar
+                            VirtualKeyCode::Left => state.spaceship_rotation.y -= 0.05, // Rotar izquierda
+                            VirtualKeyCode::Right => state.spaceship_rotation.y += 0.05, // Rotar derecha
+                            VirtualKeyCode::Up => state.spaceship_rotation.x -= 0.05, // Rotar arriba
+                            VirtualKeyCode::Down => state.spaceship_rotation.x += 0.05, // Rotar abajo
+                            _ => {}
                         }
                     }
                     _ => {}
@@ -912,10 +1435,8 @@ async fn run() {
                         // Fase de fade out (desaparecer)
                         state.spaceship.uniforms.color[3] = 1.0 - (warp_time / (WARP_DURATION / 2.0));
                     } else if warp_time < WARP_DURATION {
-                        // Fase de fade in (reaparecer)
-                        if state.spaceship_position != target_position {
-                            state.spaceship_position = target_position; // Mover a la posición objetivo
-                        }
+                        // Fase de fade in (reaparecer): la nave acompaña al cuerpo, que sigue en movimiento
+                        state.spaceship_position = state.warp_position(warp_target);
                         state.spaceship.uniforms.color[3] = (warp_time - WARP_DURATION / 2.0) / (WARP_DURATION / 2.0);
                     } else {
                         // Finaliza el warping
@@ -924,44 +1445,139 @@ async fn run() {
                     }
                 }
 
-                // Actualizar planetas
-                for sphere in &mut state.spheres {
-                    let mut uniforms = sphere.uniforms;
-                    uniforms.time = current_time;
+                // Posiciones de todos los cuerpos: los hijos orbitan alrededor de sus padres
+                state.scene_graph.update(current_time);
 
-                    if uniforms.orbital_speed > 0.0 {
-                        let angle = current_time * uniforms.orbital_speed;
-                        let x = uniforms.orbital_radius * angle.cos();
-                        let z = uniforms.orbital_radius * angle.sin();
+                // Nave: choques con los cuerpos, aterrizaje y despegue (el warp la saca del suelo)
+                let flight_orientation = cgmath::Quaternion::from(
+                    cgmath::Matrix3::from_angle_y(cgmath::Rad(state.spaceship_rotation.y))
+                        * cgmath::Matrix3::from_angle_x(cgmath::Rad(state.spaceship_rotation.x))
+                        * cgmath::Matrix3::from_angle_z(cgmath::Rad(state.spaceship_rotation.z)),
+                );
+                if is_warping {
+                    state.lander.cancel();
+                    state.spaceship_orientation = flight_orientation;
+                } else {
+                    let size = state.spaceship.radius * SPACESHIP_SCALE;
+                    let (orientation, event) = state.lander.update(
+                        state.scene_graph.nodes(),
+                        &mut state.spaceship_position,
+                        flight_orientation,
+                        size,
+                        0.016,
+                    );
+                    state.spaceship_orientation = orientation;
+                    match event {
+                        Some(LandingEvent::Touchdown { body, speed }) => {
+                            let landing = if speed <= landing::SAFE_TOUCHDOWN_SPEED { "suave" } else { "brusco" };
+                            window.set_title(&format!("Aterrizaje {} en {} a {:.3} u/s", landing, body, speed));
+                        }
+                        Some(LandingEvent::Rejected { body, reason }) => {
+                            window.set_title(&format!("No se puede aterrizar en {}: {}", body, reason));
+                        }
+                        Some(LandingEvent::Liftoff { body }) => window.set_title(&format!("Despegue de {}", body)),
+                        None => {}
+                    }
+                }
 
-                        let translation = cgmath::Matrix4::from_translation(cgmath::Vector3::new(x, 0.0, z));
-                        let rotation = cgmath::Matrix4::from_angle_y(cgmath::Rad(angle));
-                        let scale = cgmath::Matrix4::from_scale(uniforms.model[0][0]);
+                // Cámara: las estrellas solo usan su rotación para rodear al observador
+                let (view, proj) = state.camera_matrices();
+                state.camera_view = view;
+                state.camera_proj = proj;
+                let view_proj: [[f32; 4]; 4] = (proj * view).into();
+                let camera_position: [f32; 4] = cgmath::SquareMatrix::invert(&view).map_or([0.0, 0.0, 0.0, 1.0], |inverse| inverse.w.into());
+                let mut sky_view = view;
+                sky_view.w = cgmath::Vector4::new(0.0, 0.0, 0.0, 1.0);
+                state.stars.update(&state.queue, proj * sky_view);
+                if let Some(skybox) = &state.skybox {
+                    skybox.update(&state.queue, proj * sky_view);
+                }
 
-                        uniforms.model = (translation * rotation * scale).into();
+                // Actualizar planetas
+                let eye = cgmath::Vector3::new(camera_position[0], camera_position[1], camera_position[2]);
+                let viewport_height = state.config.height as f32;
+                for (sphere, node) in state.spheres.iter_mut().zip(state.scene_graph.nodes()) {
+                    // Nivel de detalle según el tamaño del cuerpo en pantalla
+                    let distance = cgmath::InnerSpace::magnitude(node.position - eye);
+                    let fov_y = cgmath::Rad::from(CAMERA_FOV_Y).0;
+                    let radius = lod::projected_radius(node.scale, distance, fov_y, viewport_height);
+                    sphere.lod = lod::select_level(sphere.lod, radius);
+
+                    // Terreno: la cámara pasa al marco del cuerpo, donde el radio vale 1
+                    if let Some(terrain) = &mut sphere.terrain {
+                        let local_eye = cgmath::SquareMatrix::invert(&node.model)
+                            .map_or(cgmath::Vector4::new(0.0, 0.0, 0.0, 1.0), |inverse| inverse * eye.extend(1.0));
+                        terrain.update(&state.device, local_eye.truncate());
                     }
 
+                    let mut uniforms = sphere.uniforms;
+                    uniforms.time = current_time;
+                    uniforms.view_proj = view_proj;
+                    uniforms.camera_position = camera_position;
+                    uniforms.model = node.model.into();
+
                     state.queue.write_buffer(
                         &sphere.uniform_buffer,
                         0,
                         bytemuck::cast_slice(&[uniforms]),
                     );
+
+                    if let Some(rings) = &mut sphere.rings {
+                        rings.update(&state.queue, view_proj, node.position, current_time);
+                    }
+                    if let Some(clouds) = &mut sphere.clouds {
+                        clouds.update(&state.queue, view_proj, camera_position, node.position, current_time);
+                    }
+                    if let Some(atmosphere) = &mut sphere.atmosphere {
+                        atmosphere.update(&state.queue, view_proj, camera_position, node.position);
+                    }
+                    if let Some(corona) = &mut sphere.corona {
+                        corona.update(&state.queue, view, proj, node.position, current_time);
+                    }
+                }
+
+                let frustum = Frustum::from_matrix(state.camera_proj * state.camera_view);
+                state.belts.update(&state.queue, view_proj, &frustum, current_time);
+                state.comets.update(&state.queue, view, proj, current_time);
+                state.post_process.update(&state.queue);
+
+                if state.show_axes {
+                    state.update_axes(view_proj);
+                }
+
+                // Durante el warping la nave salta: su estela empieza de nuevo
+                if is_warping {
+                    state.spaceship_trail.clear();
+                }
+                state.record_trails();
+                if state.show_orbits {
+                    state.update_orbits(view_proj);
                 }
 
                 // Actualizar nave espacial
                 let translation = cgmath::Matrix4::from_translation(state.spaceship_position);
-                let rotation = cgmath::Matrix4::from_angle_y(cgmath::Rad(state.spaceship_rotation.y))
-                    * cgmath::Matrix4::from_angle_x(cgmath::Rad(state.spaceship_rotation.x))
-                    * cgmath::Matrix4::from_angle_z(cgmath::Rad(state.spaceship_rotation.z));
-                let scale = cgmath::Matrix4::from_scale(0.2);
+                let rotation = cgmath::Matrix4::from(state.spaceship_orientation);
+                let scale = cgmath::Matrix4::from_scale(SPACESHIP_SCALE);
 
                 state.spaceship.uniforms.model = (translation * rotation * scale).into();
+                state.spaceship.uniforms.view_proj = view_proj;
+                state.spaceship.uniforms.camera_position = camera_position;
                 state.queue.write_buffer(
                     &state.spaceship.uniform_buffer,
                     0,
                     bytemuck::cast_slice(&[state.spaceship.uniforms]),
                 );
 
+                // Sombras: cuerpos y anillos en su posición actual y shadow map sobre la nave
+                state.update_shadows();
+
+                // Qué se dibuja este frame; con I las cuentas se muestran cada medio segundo
+                state.cull(&frustum);
+                stats_frame = (stats_frame + 1) % 30;
+                if state.show_culling && stats_frame == 0 {
+                    window.set_title(&state.culling.summary());
+                }
+
                 // Solicitar redibujo
                 window.request_redraw();
             }
@@ -972,3 +1588,25 @@ async fn run() {
         }
     });
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type ObjModel<T> = (Vec<[f32; 3]>, Vec<T>);
+
+    // Tamaño del tipo de índice que devuelve el cargador, deducido de su firma
+    fn index_size<T>(_loader: fn(&str) -> ObjModel<T>) -> usize {
+        std::mem::size_of::<T>()
+    }
+
+    #[test]
+    fn spaceship_index_format_matches_the_loader() {
+        let format_size = match SPACESHIP_INDEX_FORMAT {
+            wgpu::IndexFormat::Uint16 => 2,
+            wgpu::IndexFormat::Uint32 => 4,
+        };
+        assert_eq!(index_size(load_obj_model), format_size);
+    }
+}
@@ -0,0 +1,131 @@
+// Material parametrizado de los planetas: un solo shader (planet.wgsl) y un bloque
+// de uniforms por cuerpo, definido en la escena.
+
+use serde::Deserialize;
+
+use crate::clouds::{angular_speed, CloudParams};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaterialKind {
+    Rocky,
+    GasGiant,
+    Ocean,
+    IceGiant,
+    Star,
+}
+
+impl MaterialKind {
+    // Debe coincidir con las constantes KIND_* de planet.wgsl
+    fn shader_id(self) -> u32 {
+        match self {
+            MaterialKind::Rocky => 0,
+            MaterialKind::GasGiant => 1,
+            MaterialKind::Ocean => 2,
+            MaterialKind::IceGiant => 3,
+            MaterialKind::Star => 4,
+        }
+    }
+}
+
+// Significado de los colores según el tipo:
+// rocky: base, relieve claro, cráteres | gas_giant: zonas, cinturones, tormentas
+// ocean: agua, tierra, montañas | ice_giant: base, bandas, brillo | star: manchas y limbo, gránulos, bordes de los gránulos
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct MaterialParams {
+    pub kind: MaterialKind,
+    pub colors: [[f32; 3]; 3],
+    pub band_count: f32,       // Bandas de latitud (gigantes gaseosos y de hielo)
+    pub noise_frequency: f32,  // Frecuencia del patrón principal
+    pub detail_frequency: f32, // Frecuencia del detalle fino
+    pub ambient: f32,
+    pub emission: f32, // Intensidad emisiva (estrellas)
+    pub seed: u32,     // Variación del ruido propia de cada cuerpo
+    // Luces de ciudades en el hemisferio nocturno (solo sobre tierra firme en los mundos oceánicos)
+    pub city_color: [f32; 3],
+    pub city_intensity: f32, // 0 = sin ciudades
+    pub city_density: f32,   // Fracción de la tierra poblada, de 0 a 1
+    // Reflejo del Sol sobre el agua (mundos oceánicos)
+    pub specular: f32, // 0 = sin reflejo
+    pub shininess: f32,
+}
+
+impl Default for MaterialParams {
+    fn default() -> Self {
+        Self {
+            kind: MaterialKind::Rocky,
+            colors: [[0.5, 0.4, 0.3], [0.7, 0.6, 0.5], [0.3, 0.25, 0.2]],
+            band_count: 0.0,
+            noise_frequency: 2.5,
+            detail_frequency: 6.0,
+            ambient: 0.2,
+            emission: 0.0,
+            seed: 0,
+            city_color: [1.0, 0.75, 0.4],
+            city_intensity: 0.0,
+            city_density: 0.5,
+            specular: 0.0,
+            shininess: 60.0,
+        }
+    }
+}
+
+// Los campos `cloud_*` describen la capa de nubes del cuerpo (si tiene) para sombrear el suelo
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MaterialUniforms {
+    colors: [[f32; 4]; 3],
+    city_color: [f32; 4], // Color (rgb) e intensidad (a) de las luces nocturnas
+    kind: u32,
+    band_count: f32,
+    noise_frequency: f32,
+    detail_frequency: f32,
+    ambient: f32,
+    emission: f32,
+    seed: u32,
+    cloud_coverage: f32, // 0 = sin capa de nubes
+    cloud_height: f32,
+    cloud_drift: f32, // Velocidad angular de la capa respecto a la superficie
+    cloud_shadow: f32,
+    cloud_seed: u32,
+    city_density: f32,
+    specular: f32,
+    shininess: f32,
+    _padding: f32,
+}
+
+impl MaterialUniforms {
+    pub fn new(params: &MaterialParams, clouds: Option<&CloudParams>, rotation_period: f32) -> Self {
+        let rgba = |[r, g, b]: [f32; 3]| [r, g, b, 1.0];
+        let [r, g, b] = params.city_color;
+        let mut uniforms = Self {
+            colors: params.colors.map(rgba),
+            city_color: [r, g, b, params.city_intensity],
+            kind: params.kind.shader_id(),
+            band_count: params.band_count,
+            noise_frequency: params.noise_frequency,
+            detail_frequency: params.detail_frequency,
+            ambient: params.ambient,
+            emission: params.emission,
+            seed: params.seed,
+            cloud_coverage: 0.0,
+            cloud_height: 1.0,
+            cloud_drift: 0.0,
+            cloud_shadow: 0.0,
+            cloud_seed: 0,
+            city_density: params.city_density.clamp(0.0, 1.0),
+            specular: params.specular,
+            shininess: params.shininess.max(1.0),
+            _padding: 0.0,
+        };
+        if let Some(clouds) = clouds {
+            uniforms.cloud_coverage = clouds.coverage.clamp(0.0, 1.0);
+            uniforms.cloud_height = clouds.height.max(1.001);
+            uniforms.cloud_drift = angular_speed(clouds.rotation_period) - angular_speed(rotation_period);
+            uniforms.cloud_shadow = (clouds.shadow * clouds.opacity).clamp(0.0, 1.0);
+            uniforms.cloud_seed = clouds.seed;
+        }
+        uniforms
+    }
+}
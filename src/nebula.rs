@@ -0,0 +1,162 @@
+use wgpu::util::DeviceExt;
+
+use crate::scene::BackgroundParams;
+use crate::shaders;
+
+// Formato del cubemap horneado (admite valores por encima de 1)
+const NEBULA_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct NebulaUniforms {
+    colors: [[f32; 4]; 3],
+    face: u32,
+    seed: u32,
+    density: f32,
+    scale: f32,
+    brightness: f32,
+    band_width: f32,
+    galactic_tilt: f32,
+    core_longitude: f32,
+}
+
+// Hornea una sola vez las nebulosas y la banda galáctica en un cubemap para el skybox
+pub fn bake_nebula(device: &wgpu::Device, queue: &wgpu::Queue, params: &BackgroundParams) -> wgpu::Texture {
+    // Cada cara es una textura 2D: no puede pasar del límite del dispositivo
+    let max_size = device.limits().max_texture_dimension_2d;
+    let size = params.nebula_resolution.clamp(1, max_size);
+    if size < params.nebula_resolution {
+        eprintln!(
+            "nebula_resolution {} supera el máximo del dispositivo; se usa {}",
+            params.nebula_resolution, size
+        );
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Nebula Cubemap"),
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 6,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: NEBULA_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+
+    let [c0, c1, c2] = params.nebula_colors;
+    let mut uniforms = NebulaUniforms {
+        colors: [[c0[0], c0[1], c0[2], 1.0], [c1[0], c1[1], c1[2], 1.0], [c2[0], c2[1], c2[2], 1.0]],
+        face: 0,
+        seed: params.seed,
+        density: params.nebula_density,
+        scale: params.nebula_scale,
+        brightness: params.nebula_brightness,
+        band_width: params.band_width.to_radians(),
+        galactic_tilt: params.galactic_tilt.to_radians(),
+        core_longitude: params.core_longitude.to_radians(),
+    };
+
+    let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Nebula Uniform Buffer"),
+        contents: bytemuck::cast_slice(&[uniforms]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Nebula Bind Group Layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }],
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Nebula Bind Group"),
+        layout: &bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: uniform_buffer.as_entire_binding(),
+        }],
+    });
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Nebula Shader"),
+        source: wgpu::ShaderSource::Wgsl(shaders::compose("nebula.wgsl", &[]).into()),
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Nebula Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Nebula Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: NEBULA_FORMAT,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    });
+
+    // Una pasada por cara; cada envío ve el uniform escrito justo antes
+    for face in 0..6 {
+        uniforms.face = face;
+        queue.write_buffer(&uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+        let face_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Nebula Face View"),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_array_layer: face,
+            array_layer_count: Some(1),
+            ..Default::default()
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Nebula Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Nebula Bake Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &face_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    texture
+}
@@ -0,0 +1,230 @@
+// Versión en la CPU de las funciones de noise.wgsl que dan forma al relieve. Siguen el
+// WGSL paso a paso para que el terreno coincida con los colores que pinta el shader.
+
+use cgmath::{ElementWise, InnerSpace, Vector3};
+
+fn fract(x: f32) -> f32 {
+    x - x.floor()
+}
+
+fn fract3(v: Vector3<f32>) -> Vector3<f32> {
+    v.map(fract)
+}
+
+fn splat(x: f32) -> Vector3<f32> {
+    Vector3::new(x, x, x)
+}
+
+// Hash sin senos (Dave Hoskins): tres valores pseudoaleatorios en [0, 1)
+fn hash33(p: Vector3<f32>) -> Vector3<f32> {
+    let mut q = fract3(p.mul_element_wise(Vector3::new(0.1031, 0.1030, 0.0973)));
+    q += splat(q.dot(Vector3::new(q.y, q.x, q.z) + splat(33.33)));
+    fract3(
+        (Vector3::new(q.x, q.x, q.y) + Vector3::new(q.y, q.x, q.x)).mul_element_wise(Vector3::new(q.z, q.y, q.x)),
+    )
+}
+
+// Desplazamiento del dominio propio de cada semilla, para que dos cuerpos no se repitan
+pub fn seed_offset(seed: u32) -> Vector3<f32> {
+    let s = (seed % 4096) as f32;
+    hash33(Vector3::new(s + 0.5, s * 1.37 + 11.0, s * 2.11 + 47.0)) * 512.0
+}
+
+fn mod289(x: f32) -> f32 {
+    x - (x * (1.0 / 289.0)).floor() * 289.0
+}
+
+fn permute(x: [f32; 4]) -> [f32; 4] {
+    x.map(|x| mod289((x * 34.0 + 1.0) * x))
+}
+
+fn add4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]]
+}
+
+// Ruido simplex 3D (Ashima Arts / Stefan Gustavson, licencia MIT)
+pub fn simplex3(v: Vector3<f32>) -> f32 {
+    const C: (f32, f32) = (1.0 / 6.0, 1.0 / 3.0);
+
+    // Primera esquina
+    let i = (v + splat((v.x + v.y + v.z) * C.1)).map(f32::floor);
+    let x0 = v - i + splat((i.x + i.y + i.z) * C.0);
+
+    // Otras esquinas
+    let step = |edge: f32, x: f32| if x < edge { 0.0 } else { 1.0 };
+    let g = Vector3::new(step(x0.y, x0.x), step(x0.z, x0.y), step(x0.x, x0.z));
+    let l = splat(1.0) - g;
+    let i1 = Vector3::new(g.x.min(l.z), g.y.min(l.x), g.z.min(l.y));
+    let i2 = Vector3::new(g.x.max(l.z), g.y.max(l.x), g.z.max(l.y));
+
+    let x1 = x0 - i1 + splat(C.0);
+    let x2 = x0 - i2 + splat(C.1);
+    let x3 = x0 - splat(0.5);
+
+    // Permutaciones
+    let i = i.map(mod289);
+    let p = permute(add4(
+        permute(add4(
+            permute(add4([i.z; 4], [0.0, i1.z, i2.z, 1.0])),
+            add4([i.y; 4], [0.0, i1.y, i2.y, 1.0]),
+        )),
+        add4([i.x; 4], [0.0, i1.x, i2.x, 1.0]),
+    ));
+
+    // Gradientes: 7x7 puntos sobre un cuadrado, proyectados en un octaedro
+    let n = 1.0 / 7.0;
+    let ns = (n * 2.0, n * 0.5 - 1.0, n);
+
+    let corners = [x0, x1, x2, x3];
+    let mut sum = 0.0;
+    for (k, corner) in corners.iter().enumerate() {
+        let j = p[k] - 49.0 * (p[k] * ns.2 * ns.2).floor();
+        let x_ = (j * ns.2).floor();
+        let y_ = (j - 7.0 * x_).floor();
+        let x = x_ * ns.0 + ns.1;
+        let y = y_ * ns.0 + ns.1;
+        let h = 1.0 - x.abs() - y.abs();
+
+        // sh = -step(h, 0): los gradientes fuera del octaedro se pliegan hacia adentro
+        let sh = if 0.0 < h { 0.0 } else { -1.0 };
+        let gradient = Vector3::new(
+            x + ((x.floor() * 2.0 + 1.0) * sh),
+            y + ((y.floor() * 2.0 + 1.0) * sh),
+            h,
+        );
+
+        // Normalización de los gradientes
+        let norm = 1.792_842_9 - 0.853_734_72 * gradient.dot(gradient);
+        let m = (0.6 - corner.dot(*corner)).max(0.0);
+        let m = m * m;
+        sum += m * m * (gradient * norm).dot(*corner);
+    }
+    42.0 * sum
+}
+
+// Movimiento browniano fraccional sobre ruido simplex
+pub fn fbm3(p: Vector3<f32>, octaves: i32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 0.5;
+    let mut normalization = 0.0;
+    let mut position = p;
+    for _ in 0..octaves {
+        sum += amplitude * simplex3(position);
+        normalization += amplitude;
+        position = position * 2.02 + Vector3::new(17.3, 5.1, 9.7);
+        amplitude *= 0.5;
+    }
+    sum / normalization
+}
+
+// fBm con crestas: picos afilados para cordilleras y tormentas; devuelve [0, 1]
+pub fn ridged3(p: Vector3<f32>, octaves: i32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 0.5;
+    let mut normalization = 0.0;
+    let mut weight = 1.0;
+    let mut position = p;
+    for _ in 0..octaves {
+        let mut ridge = 1.0 - simplex3(position).abs();
+        ridge = ridge * ridge * weight;
+        weight = (ridge * 2.0).clamp(0.0, 1.0);
+        sum += amplitude * ridge;
+        normalization += amplitude;
+        position = position * 2.03 + Vector3::new(3.7, 11.9, 1.3);
+        amplitude *= 0.5;
+    }
+    sum / normalization
+}
+
+// Deformación de dominio: desplaza el punto con otro fBm para formas más orgánicas
+pub fn domain_warp3(p: Vector3<f32>, strength: f32) -> Vector3<f32> {
+    let offset = Vector3::new(
+        fbm3(p, 3),
+        fbm3(p + Vector3::new(5.2, 1.3, 2.8), 3),
+        fbm3(p + Vector3::new(1.7, 9.2, 4.1), 3),
+    );
+    p + offset * strength
+}
+
+// Ruido celular (Worley): distancia al punto más cercano (x) y al segundo (y)
+pub fn worley3(p: Vector3<f32>) -> (f32, f32) {
+    let cell = p.map(f32::floor);
+    let local = fract3(p);
+    let mut f1: f32 = 8.0;
+    let mut f2: f32 = 8.0;
+    for z in -1..=1 {
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let neighbor = Vector3::new(x as f32, y as f32, z as f32);
+                let feature = neighbor + hash33(cell + neighbor) - local;
+                let squared = feature.magnitude2();
+                if squared < f1 {
+                    f2 = f1;
+                    f1 = squared;
+                } else if squared < f2 {
+                    f2 = squared;
+                }
+            }
+        }
+    }
+    (f1.sqrt(), f2.sqrt())
+}
+
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Valores de referencia de noise.wgsl, calculados transcribiendo el WGSL operación por
+    // operación con redondeo a f32; la versión en la CPU debe reproducirlos
+    fn assert_close(actual: f32, expected: f32) {
+        let tolerance = 1e-5 * expected.abs().max(1.0);
+        assert!((actual - expected).abs() <= tolerance, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn seed_offset_matches_the_shader() {
+        let cases = [
+            (0, [172.3125, 323.25, 277.75]),
+            (1, [193.625, 493.25, 28.625]),
+            (7, [501.4375, 331.9375, 481.0625]),
+            (4097, [193.625, 493.25, 28.625]), // La semilla se toma módulo 4096
+        ];
+        for (seed, expected) in cases {
+            let offset = seed_offset(seed);
+            for (actual, expected) in [offset.x, offset.y, offset.z].into_iter().zip(expected) {
+                assert_close(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn simplex3_matches_the_shader() {
+        let cases = [
+            (Vector3::new(0.3, -1.7, 2.25), -0.273_923_8),
+            (Vector3::new(12.5, 3.1, -7.8), -0.629_675),
+            (Vector3::new(-0.41, 0.0, 5.93), -0.454_244_9),
+        ];
+        for (p, expected) in cases {
+            assert_close(simplex3(p), expected);
+        }
+    }
+
+    #[test]
+    fn worley3_matches_the_shader() {
+        let cases = [
+            (Vector3::new(0.3, -1.7, 2.25), (0.556_244_6, 0.594_165_3)),
+            (Vector3::new(12.5, 3.1, -7.8), (0.166_307_06, 0.565_741)),
+            (Vector3::new(-0.41, 0.0, 5.93), (0.626_682_6, 0.844_208_5)),
+        ];
+        for (p, (f1, f2)) in cases {
+            let (actual_f1, actual_f2) = worley3(p);
+            assert_close(actual_f1, f1);
+            assert_close(actual_f2, f2);
+        }
+    }
+}
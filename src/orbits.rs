@@ -0,0 +1,191 @@
+// Órbitas elípticas (radio, excentricidad, inclinación y periapsis) y estelas de posiciones recientes
+
+use std::collections::VecDeque;
+
+use std::f32::consts::TAU;
+
+use cgmath::{Deg, Matrix3, Vector3};
+
+use crate::lines::LineVertex;
+use crate::scene::BodyParams;
+
+const ORBIT_SEGMENTS: usize = 128;
+
+// Más allá de esta excentricidad el solver de Kepler deja de converger en pocas iteraciones
+pub const MAX_ECCENTRICITY: f32 = 0.99;
+
+// Para Scene::load: la excentricidad de la órbita de `name` debe ser una elipse que el solver resuelva
+pub fn validate_eccentricity(name: &str, eccentricity: f32) -> Result<(), String> {
+    if (0.0..=MAX_ECCENTRICITY).contains(&eccentricity) {
+        Ok(())
+    } else {
+        Err(format!("{}: eccentricity debe estar entre 0 y {}", name, MAX_ECCENTRICITY))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Orbit {
+    pub semi_major_axis: f32,
+    pub speed: f32, // Radianes de anomalía media por segundo
+    pub eccentricity: f32,
+    plane: Matrix3<f32>, // Periapsis e inclinación respecto al plano XZ del padre
+}
+
+impl Orbit {
+    // Ángulos en grados
+    pub fn new(semi_major_axis: f32, speed: f32, eccentricity: f32, inclination: f32, periapsis: f32) -> Self {
+        Self {
+            semi_major_axis,
+            speed,
+            eccentricity,
+            plane: Matrix3::from_angle_x(Deg(inclination)) * Matrix3::from_angle_y(Deg(-periapsis)),
+        }
+    }
+
+    pub fn from_body(body: &BodyParams) -> Self {
+        Self::new(
+            body.orbital_radius,
+            body.orbital_speed,
+            body.eccentricity,
+            body.inclination,
+            body.periapsis,
+        )
+    }
+
+    // Punto de la elipse para la anomalía excéntrica `anomaly`, relativo al foco (el padre)
+    fn point(&self, anomaly: f32) -> Vector3<f32> {
+        let a = self.semi_major_axis;
+        let b = a * (1.0 - self.eccentricity * self.eccentricity).sqrt();
+        self.plane * Vector3::new(a * (anomaly.cos() - self.eccentricity), 0.0, b * anomaly.sin())
+    }
+
+    // Posición en el instante `time`
+    pub fn position(&self, time: f32) -> Vector3<f32> {
+        // Se envuelve a una vuelta para no perder precisión cuando el tiempo crece
+        self.point(self.eccentric_anomaly((time * self.speed).rem_euclid(TAU)))
+    }
+
+    // Resuelve la ecuación de Kepler (M = E - e·sen E) por Newton
+    fn eccentric_anomaly(&self, mean_anomaly: f32) -> f32 {
+        // Estimación inicial de Danby: converge también con las excentricidades altas de los cometas
+        let mut anomaly = mean_anomaly + 0.85 * self.eccentricity * mean_anomaly.sin().signum();
+        for _ in 0..6 {
+            anomaly -= (anomaly - self.eccentricity * anomaly.sin() - mean_anomaly)
+                / (1.0 - self.eccentricity * anomaly.cos());
+        }
+        anomaly
+    }
+
+    // Distancia al padre en el punto más cercano de la órbita
+    pub fn periapsis_distance(&self) -> f32 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    // Segmentos de la elipse completa alrededor de `center`
+    pub fn append_path(&self, center: Vector3<f32>, color: [f32; 4], vertices: &mut Vec<LineVertex>) {
+        if self.semi_major_axis <= 0.0 {
+            return;
+        }
+        let step = TAU / ORBIT_SEGMENTS as f32;
+        for i in 0..ORBIT_SEGMENTS {
+            for anomaly in [i as f32 * step, (i + 1) as f32 * step] {
+                vertices.push(LineVertex {
+                    position: (center + self.point(anomaly)).into(),
+                    color,
+                });
+            }
+        }
+    }
+
+    pub fn path_vertex_count() -> usize {
+        ORBIT_SEGMENTS * 2
+    }
+}
+
+// Últimas posiciones de un objeto; los segmentos más viejos se desvanecen
+pub struct Trail {
+    points: VecDeque<Vector3<f32>>,
+    length: usize,
+}
+
+impl Trail {
+    pub fn new(length: usize) -> Self {
+        Self {
+            points: VecDeque::with_capacity(length),
+            length,
+        }
+    }
+
+    pub fn push(&mut self, point: Vector3<f32>) {
+        if self.points.len() == self.length {
+            self.points.pop_front();
+        }
+        self.points.push_back(point);
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn append_path(&self, color: [f32; 4], vertices: &mut Vec<LineVertex>) {
+        let count = self.points.len();
+        for (i, (from, to)) in self.points.iter().zip(self.points.iter().skip(1)).enumerate() {
+            for (point, age) in [(from, i), (to, i + 1)] {
+                let fade = age as f32 / count as f32;
+                vertices.push(LineVertex {
+                    position: (*point).into(),
+                    color: [color[0], color[1], color[2], color[3] * fade],
+                });
+            }
+        }
+    }
+
+    // Vértices de una estela completa de `length` muestras
+    pub fn path_vertex_count(length: usize) -> usize {
+        length.saturating_sub(1) * 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::InnerSpace;
+
+    #[test]
+    fn solves_keplers_equation() {
+        let orbit = Orbit::new(10.0, 1.0, 0.72, 0.0, 0.0);
+        for step in 0..64 {
+            let mean_anomaly = step as f32 / 64.0 * TAU;
+            let anomaly = orbit.eccentric_anomaly(mean_anomaly);
+            let residual = anomaly - 0.72 * anomaly.sin() - mean_anomaly;
+            assert!(residual.abs() < 1e-5, "M = {}: residuo {}", mean_anomaly, residual);
+        }
+    }
+
+    #[test]
+    fn circular_orbits_keep_their_radius() {
+        let orbit = Orbit::new(4.0, 0.7, 0.0, 20.0, 45.0);
+        for step in 0..32 {
+            assert!((orbit.position(step as f32 * 0.37).magnitude() - 4.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn periapsis_is_the_closest_point() {
+        let orbit = Orbit::new(10.0, 1.0, 0.5, 0.0, 30.0);
+        assert!((orbit.periapsis_distance() - 5.0).abs() < 1e-6);
+        // En t = 0 el cuerpo parte del periapsis; media vuelta después está en el apoapsis
+        assert!((orbit.position(0.0).magnitude() - 5.0).abs() < 1e-4);
+        assert!((orbit.position(std::f32::consts::PI).magnitude() - 15.0).abs() < 1e-3);
+        // Una vuelta después de mucho tiempo cae en el mismo lugar
+        let late = 1000.0 * TAU;
+        assert!((orbit.position(late + 1.0) - orbit.position(1.0)).magnitude() < 1e-2);
+    }
+
+    #[test]
+    fn rejects_open_orbits() {
+        assert!(validate_eccentricity("Halley", 0.967).is_ok());
+        assert!(validate_eccentricity("Halley", 1.0).unwrap_err().starts_with("Halley:"));
+        assert!(validate_eccentricity("Halley", -0.1).is_err());
+    }
+}
@@ -0,0 +1,399 @@
+// Posprocesado: la escena se dibuja en una textura HDR (Rgba16Float); de ella se extraen
+// las zonas brillantes, se difuminan con una cadena de bloom a resoluciones decrecientes
+// y el tonemapping con exposición las combina en la imagen final de la swapchain.
+// Con MSAA la escena se dibuja en una copia multisample de la textura HDR; con FXAA el
+// tonemapping escribe en una textura intermedia que el FXAA suaviza sobre la swapchain.
+
+use wgpu::util::DeviceExt;
+
+use crate::settings::Settings;
+use crate::shaders;
+
+// Formato en el que dibujan todos los pases de la escena
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+// Niveles de la cadena de bloom; el primero tiene la mitad de la resolución de la ventana
+pub const BLOOM_LEVELS: usize = 6;
+
+// Solo aporta al bloom lo que supera este brillo (con una transición suave de ancho `knee`)
+const BLOOM_THRESHOLD: f32 = 1.0;
+const BLOOM_KNEE: f32 = 0.5;
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostUniforms {
+    threshold: f32,
+    knee: f32,
+    intensity: f32,
+    exposure: f32,
+    filter_radius: f32, // Radio del filtro de upsampling, en texels del nivel de origen
+    _padding: [f32; 3],
+}
+
+pub struct PostProcess {
+    prefilter_pipeline: wgpu::RenderPipeline,
+    downsample_pipeline: wgpu::RenderPipeline,
+    upsample_pipeline: wgpu::RenderPipeline, // Suma el nivel inferior sobre el superior
+    tonemap_pipeline: wgpu::RenderPipeline,
+    fxaa_pipeline: wgpu::RenderPipeline,
+    texture_layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    pub hdr_view: wgpu::TextureView,
+    pub hdr_multisampled_view: Option<wgpu::TextureView>, // Solo con MSAA
+    pub ldr_view: wgpu::TextureView,
+    pub bloom_views: Vec<wgpu::TextureView>,
+    hdr_bind_group: wgpu::BindGroup,
+    ldr_bind_group: wgpu::BindGroup,
+    bloom_bind_groups: Vec<wgpu::BindGroup>,
+    sample_count: u32,
+    pub exposure: f32,
+    pub bloom_enabled: bool,
+    bloom_intensity: f32,
+}
+
+impl PostProcess {
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        settings: &Settings,
+        sample_count: u32,
+    ) -> Self {
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Process Uniform Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        // Una sola textura de origen; el tonemapping usa dos grupos con este layout
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Process Texture Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Process Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let uniforms = PostUniforms {
+            threshold: BLOOM_THRESHOLD,
+            knee: BLOOM_KNEE,
+            intensity: settings.bloom_intensity,
+            exposure: settings.exposure,
+            filter_radius: 1.0,
+            _padding: [0.0; 3],
+        };
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post Process Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post Process Uniform Bind Group"),
+            layout: &uniform_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post Process Shader"),
+            // El FXAA mide el contraste sobre valores con gamma; una swapchain sRGB los entrega lineales
+            source: wgpu::ShaderSource::Wgsl(
+                shaders::compose("postprocess.wgsl", if config.format.is_srgb() { &["SRGB_SURFACE"] } else { &[] }).into(),
+            ),
+        });
+
+        let bloom_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Pipeline Layout"),
+            bind_group_layouts: &[&uniform_layout, &texture_layout],
+            push_constant_ranges: &[],
+        });
+        let tonemap_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tonemap Pipeline Layout"),
+            bind_group_layouts: &[&uniform_layout, &texture_layout, &texture_layout],
+            push_constant_ranges: &[],
+        });
+
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+
+        let prefilter_pipeline =
+            Self::create_pipeline(device, &bloom_layout, &shader, "Bloom Prefilter Pipeline", "fs_prefilter", HDR_FORMAT, None);
+        let downsample_pipeline =
+            Self::create_pipeline(device, &bloom_layout, &shader, "Bloom Downsample Pipeline", "fs_downsample", HDR_FORMAT, None);
+        let upsample_pipeline = Self::create_pipeline(
+            device,
+            &bloom_layout,
+            &shader,
+            "Bloom Upsample Pipeline",
+            "fs_upsample",
+            HDR_FORMAT,
+            Some(additive),
+        );
+        let tonemap_pipeline =
+            Self::create_pipeline(device, &tonemap_layout, &shader, "Tonemap Pipeline", "fs_tonemap", config.format, None);
+        let fxaa_pipeline =
+            Self::create_pipeline(device, &bloom_layout, &shader, "FXAA Pipeline", "fs_fxaa", config.format, None);
+
+        let (hdr_view, hdr_multisampled_view, ldr_view, bloom_views) = Self::create_targets(device, config, sample_count);
+        let hdr_bind_group = Self::create_texture_bind_group(device, &texture_layout, &hdr_view);
+        let ldr_bind_group = Self::create_texture_bind_group(device, &texture_layout, &ldr_view);
+        let bloom_bind_groups = bloom_views
+            .iter()
+            .map(|view| Self::create_texture_bind_group(device, &texture_layout, view))
+            .collect();
+
+        Self {
+            prefilter_pipeline,
+            downsample_pipeline,
+            upsample_pipeline,
+            tonemap_pipeline,
+            fxaa_pipeline,
+            texture_layout,
+            uniform_buffer,
+            uniform_bind_group,
+            hdr_view,
+            hdr_multisampled_view,
+            ldr_view,
+            bloom_views,
+            hdr_bind_group,
+            ldr_bind_group,
+            bloom_bind_groups,
+            sample_count,
+            exposure: settings.exposure,
+            bloom_enabled: settings.bloom,
+            bloom_intensity: settings.bloom_intensity,
+        }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        label: &str,
+        entry_point: &str,
+        format: wgpu::TextureFormat,
+        blend: Option<wgpu::BlendState>,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_fullscreen",
+                buffers: &[], // Triángulo que cubre la pantalla generado a partir de vertex_index
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+
+    // Textura HDR del tamaño de la ventana (y su copia multisample), la intermedia del FXAA
+    // y un nivel de bloom por cada mitad de resolución
+    fn create_targets(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+    ) -> (wgpu::TextureView, Option<wgpu::TextureView>, wgpu::TextureView, Vec<wgpu::TextureView>) {
+        let create_view = |label: &str, width: u32, height: u32, format: wgpu::TextureFormat, samples: u32| {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: width.max(1),
+                    height: height.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: samples,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: if samples > 1 {
+                    wgpu::TextureUsages::RENDER_ATTACHMENT
+                } else {
+                    wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING
+                },
+                view_formats: &[],
+            });
+            texture.create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        let hdr_view = create_view("HDR Texture", config.width, config.height, HDR_FORMAT, 1);
+        let hdr_multisampled_view = (sample_count > 1).then(|| {
+            create_view("HDR Multisampled Texture", config.width, config.height, HDR_FORMAT, sample_count)
+        });
+        let ldr_view = create_view("LDR Texture", config.width, config.height, config.format, 1);
+        let bloom_views = (0..BLOOM_LEVELS)
+            .map(|level| {
+                let divisor = 2 << level;
+                create_view(
+                    &format!("Bloom Texture {}", level),
+                    config.width / divisor,
+                    config.height / divisor,
+                    HDR_FORMAT,
+                    1,
+                )
+            })
+            .collect();
+        (hdr_view, hdr_multisampled_view, ldr_view, bloom_views)
+    }
+
+    fn create_texture_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post Process Texture Bind Group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            }],
+        })
+    }
+
+    // Las texturas dependen del tamaño de la ventana
+    pub fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        let (hdr_view, hdr_multisampled_view, ldr_view, bloom_views) =
+            Self::create_targets(device, config, self.sample_count);
+        self.hdr_bind_group = Self::create_texture_bind_group(device, &self.texture_layout, &hdr_view);
+        self.ldr_bind_group = Self::create_texture_bind_group(device, &self.texture_layout, &ldr_view);
+        self.bloom_bind_groups = bloom_views
+            .iter()
+            .map(|view| Self::create_texture_bind_group(device, &self.texture_layout, view))
+            .collect();
+        self.hdr_view = hdr_view;
+        self.hdr_multisampled_view = hdr_multisampled_view;
+        self.ldr_view = ldr_view;
+        self.bloom_views = bloom_views;
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue) {
+        let uniforms = PostUniforms {
+            threshold: BLOOM_THRESHOLD,
+            knee: BLOOM_KNEE,
+            intensity: if self.bloom_enabled { self.bloom_intensity } else { 0.0 },
+            exposure: self.exposure,
+            filter_radius: 1.0,
+            _padding: [0.0; 3],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+    }
+
+    fn draw_fullscreen<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a wgpu::RenderPipeline,
+        source: &'a wgpu::BindGroup,
+    ) {
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_bind_group(1, source, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    // Extrae las zonas brillantes de la escena al nivel 0
+    pub fn draw_prefilter<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.bloom_enabled {
+            self.draw_fullscreen(render_pass, &self.prefilter_pipeline, &self.hdr_bind_group);
+        }
+    }
+
+    // Reduce el nivel `level - 1` al nivel `level`
+    pub fn draw_downsample<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, level: usize) {
+        if self.bloom_enabled {
+            self.draw_fullscreen(render_pass, &self.downsample_pipeline, &self.bloom_bind_groups[level - 1]);
+        }
+    }
+
+    // Suma el nivel `level` difuminado sobre el nivel `level - 1`
+    pub fn draw_upsample<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, level: usize) {
+        if self.bloom_enabled {
+            self.draw_fullscreen(render_pass, &self.upsample_pipeline, &self.bloom_bind_groups[level]);
+        }
+    }
+
+    // Escena HDR más bloom, con exposición y tonemapping, sobre la swapchain
+    pub fn draw_tonemap<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.tonemap_pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.hdr_bind_group, &[]);
+        render_pass.set_bind_group(2, &self.bloom_bind_groups[0], &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    // Suaviza los bordes de la imagen tonemapeada sobre la swapchain
+    pub fn draw_fxaa<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        self.draw_fullscreen(render_pass, &self.fxaa_pipeline, &self.ldr_bind_group);
+    }
+}
@@ -0,0 +1,157 @@
+// Preprocesador de WGSL: resuelve las directivas antes de crear el módulo del shader.
+//
+//   #include "archivo.wgsl"   inserta otro archivo de la biblioteca (una sola vez por módulo)
+//   #define NOMBRE            activa una bandera
+//   #ifdef NOMBRE / #ifndef NOMBRE / #else / #endif
+//
+// Las directivas ocupan una línea propia; WGSL no usa '#', así que no hay ambigüedad.
+
+use std::collections::HashSet;
+
+// Estado de un bloque #ifdef/#ifndef abierto
+struct Conditional {
+    active: bool,        // Las líneas del bloque se emiten
+    parent_active: bool, // El bloque que lo contiene estaba activo
+    has_else: bool,
+}
+
+struct Preprocessor<'a> {
+    library: &'a [(&'a str, &'a str)],
+    defines: HashSet<String>,
+    included: HashSet<&'a str>,
+    output: String,
+}
+
+// Compone el archivo `name` de la biblioteca con las banderas `defines` activadas
+pub fn preprocess(name: &str, library: &[(&str, &str)], defines: &[&str]) -> Result<String, String> {
+    let mut preprocessor = Preprocessor {
+        library,
+        defines: defines.iter().map(|define| define.to_string()).collect(),
+        included: HashSet::new(),
+        output: String::new(),
+    };
+    preprocessor.expand(name)?;
+    Ok(preprocessor.output)
+}
+
+impl<'a> Preprocessor<'a> {
+    fn expand(&mut self, name: &str) -> Result<(), String> {
+        let (name, source) = self
+            .library
+            .iter()
+            .copied()
+            .find(|(file, _)| *file == name)
+            .ok_or_else(|| format!("Shader desconocido: {}", name))?;
+
+        // Cada archivo se incluye como mucho una vez (también rompe los ciclos)
+        if !self.included.insert(name) {
+            return Ok(());
+        }
+
+        let mut conditionals: Vec<Conditional> = Vec::new();
+        for (index, line) in source.lines().enumerate() {
+            let active = conditionals.last().is_none_or(|conditional| conditional.active);
+            let error = |message: &str| format!("{}:{}: {}", name, index + 1, message);
+
+            let Some(directive) = line.trim_start().strip_prefix('#') else {
+                if active {
+                    self.output.push_str(line);
+                    self.output.push('\n');
+                }
+                continue;
+            };
+
+            let mut words = directive.split_whitespace();
+            let keyword = words.next().unwrap_or("");
+            let argument = words.next();
+            if words.next().is_some() {
+                return Err(error("argumentos de más en la directiva"));
+            }
+
+            match (keyword, argument) {
+                ("include", Some(file)) => {
+                    if active {
+                        let file = file
+                            .strip_prefix('"')
+                            .and_then(|file| file.strip_suffix('"'))
+                            .ok_or_else(|| error("#include espera un nombre entre comillas"))?;
+                        self.expand(file).map_err(|inner| error(&inner))?;
+                    }
+                }
+                ("define", Some(flag)) => {
+                    if active {
+                        self.defines.insert(flag.to_string());
+                    }
+                }
+                ("ifdef", Some(flag)) | ("ifndef", Some(flag)) => {
+                    let defined = self.defines.contains(flag);
+                    conditionals.push(Conditional {
+                        active: active && defined == (keyword == "ifdef"),
+                        parent_active: active,
+                        has_else: false,
+                    });
+                }
+                ("else", None) => {
+                    let conditional = conditionals.last_mut().ok_or_else(|| error("#else sin #ifdef"))?;
+                    if conditional.has_else {
+                        return Err(error("#else repetido"));
+                    }
+                    conditional.has_else = true;
+                    conditional.active = conditional.parent_active && !conditional.active;
+                }
+                ("endif", None) => {
+                    conditionals.pop().ok_or_else(|| error("#endif sin #ifdef"))?;
+                }
+                _ => return Err(error(&format!("directiva inválida: #{}", directive.trim()))),
+            }
+        }
+
+        if !conditionals.is_empty() {
+            return Err(format!("{}: falta #endif", name));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::preprocess;
+
+    const LIBRARY: &[(&str, &str)] = &[
+        ("common.wgsl", "const COMMON: f32 = 1.0;\n"),
+        ("a.wgsl", "#include \"common.wgsl\"\nconst A: f32 = COMMON;\n"),
+        (
+            "main.wgsl",
+            "#include \"a.wgsl\"\n#include \"common.wgsl\"\n#ifdef FAST\nconst MODE: u32 = 1u;\n#else\nconst MODE: u32 = 2u;\n#endif\n",
+        ),
+        ("nested.wgsl", "#define OUTER\n#ifdef OUTER\n#ifndef INNER\nouter\n#else\ninner\n#endif\n#endif\n"),
+        ("cycle.wgsl", "#include \"cycle.wgsl\"\nok\n"),
+        ("missing.wgsl", "#include \"nope.wgsl\"\n"),
+        ("unclosed.wgsl", "#ifdef FAST\n"),
+        ("stray.wgsl", "#endif\n"),
+    ];
+
+    #[test]
+    fn includes_each_file_once() {
+        let source = preprocess("main.wgsl", LIBRARY, &[]).unwrap();
+        assert_eq!(source.matches("const COMMON").count(), 1);
+        assert!(source.find("const COMMON").unwrap() < source.find("const A").unwrap());
+        assert_eq!(preprocess("cycle.wgsl", LIBRARY, &[]).unwrap(), "ok\n");
+    }
+
+    #[test]
+    fn selects_branches_from_defines() {
+        assert!(preprocess("main.wgsl", LIBRARY, &[]).unwrap().contains("MODE: u32 = 2u"));
+        assert!(preprocess("main.wgsl", LIBRARY, &["FAST"]).unwrap().contains("MODE: u32 = 1u"));
+        assert_eq!(preprocess("nested.wgsl", LIBRARY, &[]).unwrap(), "outer\n");
+        assert_eq!(preprocess("nested.wgsl", LIBRARY, &["INNER"]).unwrap(), "inner\n");
+    }
+
+    #[test]
+    fn reports_invalid_sources() {
+        assert!(preprocess("missing.wgsl", LIBRARY, &[]).unwrap_err().contains("nope.wgsl"));
+        assert!(preprocess("unclosed.wgsl", LIBRARY, &[]).is_err());
+        assert!(preprocess("stray.wgsl", LIBRARY, &[]).is_err());
+        assert!(preprocess("unknown.wgsl", LIBRARY, &[]).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+// Planificador de pases de render: cada pase declara su etapa, sus attachments y
+// la función que dibuja. El grafo decide el orden y las operaciones de carga
+// (el primer pase que escribe un destino lo limpia, los siguientes lo conservan).
+// Con MSAA la escena se dibuja en una versión multisample de la textura HDR que el
+// último pase que la escribe resuelve sobre la textura normal.
+
+// Etapas en el orden en que se ejecutan dentro de un frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PassStage {
+    Shadow,
+    Background,
+    Opaque,
+    Transparent,
+    Overlay,
+    PostProcess,
+}
+
+impl PassStage {
+    pub const ALL: [PassStage; 6] = [
+        PassStage::Shadow,
+        PassStage::Background,
+        PassStage::Opaque,
+        PassStage::Transparent,
+        PassStage::Overlay,
+        PassStage::PostProcess,
+    ];
+}
+
+// Textura de color en la que escribe un pase
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorTarget {
+    Surface,      // Imagen actual de la swapchain
+    Hdr,          // Escena en punto flotante, antes del tonemapping
+    Ldr,          // Imagen tonemapeada, antes del FXAA
+    Bloom(usize), // Nivel de la cadena de bloom (0 = media resolución)
+}
+
+// Textura de profundidad que usa un pase
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepthTarget {
+    Scene,  // Profundidad compartida por los pases de la escena
+    Shadow, // Shadow map, visto desde el Sol
+}
+
+// Vistas disponibles para el frame actual
+pub struct FrameTargets<'a> {
+    pub surface: &'a wgpu::TextureView,
+    pub hdr: &'a wgpu::TextureView,
+    pub hdr_multisampled: Option<&'a wgpu::TextureView>, // Destino real de la escena con MSAA
+    pub ldr: &'a wgpu::TextureView,
+    pub bloom: &'a [wgpu::TextureView],
+    pub depth: &'a wgpu::TextureView,
+    pub shadow: &'a wgpu::TextureView,
+}
+
+impl<'a> FrameTargets<'a> {
+    fn color_view(&self, target: ColorTarget) -> &'a wgpu::TextureView {
+        match target {
+            ColorTarget::Surface => self.surface,
+            ColorTarget::Hdr => self.hdr,
+            ColorTarget::Ldr => self.ldr,
+            ColorTarget::Bloom(level) => &self.bloom[level],
+        }
+    }
+
+    fn depth_view(&self, target: DepthTarget) -> &'a wgpu::TextureView {
+        match target {
+            DepthTarget::Scene => self.depth,
+            DepthTarget::Shadow => self.shadow,
+        }
+    }
+}
+
+// Función de dibujo de un pase; recibe el contexto (normalmente `State`).
+// Puede capturar datos propios del pase, como el nivel de bloom que procesa.
+pub type DrawFn<C> = Box<dyn for<'a> Fn(&'a C, &mut wgpu::RenderPass<'a>)>;
+
+// Envuelve una función o un closure como `DrawFn` (fija la firma que el closure no puede inferir solo)
+pub fn draw_fn<C>(draw: impl for<'a> Fn(&'a C, &mut wgpu::RenderPass<'a>) + 'static) -> DrawFn<C> {
+    Box::new(draw)
+}
+
+pub struct PassNode<C> {
+    pub name: &'static str,
+    pub stage: PassStage,
+    pub color: Option<ColorTarget>, // None = solo profundidad (shadow map)
+    pub depth: Option<DepthTarget>,
+    pub draw: DrawFn<C>,
+}
+
+pub struct RenderGraph<C> {
+    passes: Vec<PassNode<C>>,
+    clear_color: wgpu::Color,
+}
+
+impl<C> RenderGraph<C> {
+    pub fn new(clear_color: wgpu::Color) -> Self {
+        Self {
+            passes: Vec::new(),
+            clear_color,
+        }
+    }
+
+    // Los pases de una misma etapa se ejecutan en el orden en que se agregan
+    pub fn add_pass(&mut self, pass: PassNode<C>) {
+        self.passes.push(pass);
+    }
+
+    pub fn execute(&self, context: &C, encoder: &mut wgpu::CommandEncoder, targets: &FrameTargets) {
+        for step in plan_passes(&self.passes, targets.hdr_multisampled.is_some()) {
+            let pass = &self.passes[step.pass];
+
+            let color_attachment = pass.color.map(|target| {
+                let load = if step.clear_color {
+                    wgpu::LoadOp::Clear(self.clear_color)
+                } else {
+                    wgpu::LoadOp::Load
+                };
+                match (target, targets.hdr_multisampled) {
+                    (ColorTarget::Hdr, Some(multisampled)) => wgpu::RenderPassColorAttachment {
+                        view: multisampled,
+                        resolve_target: step.resolve.then_some(targets.hdr),
+                        ops: wgpu::Operations { load, store: !step.resolve },
+                    },
+                    _ => wgpu::RenderPassColorAttachment {
+                        view: targets.color_view(target),
+                        resolve_target: None,
+                        ops: wgpu::Operations { load, store: true },
+                    },
+                }
+            });
+
+            let depth_stencil_attachment = pass.depth.map(|target| {
+                let load = if step.clear_depth {
+                    wgpu::LoadOp::Clear(1.0)
+                } else {
+                    wgpu::LoadOp::Load
+                };
+                wgpu::RenderPassDepthStencilAttachment {
+                    view: targets.depth_view(target),
+                    depth_ops: Some(wgpu::Operations { load, store: true }),
+                    stencil_ops: None,
+                }
+            });
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(pass.name),
+                color_attachments: &[color_attachment],
+                depth_stencil_attachment,
+            });
+
+            (pass.draw)(context, &mut render_pass);
+        }
+    }
+}
+
+// Decisiones del grafo para un pase, separadas de wgpu para poder probarlas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PassStep {
+    pass: usize,       // Índice en el orden en que se agregaron los pases
+    clear_color: bool, // Primer pase que escribe su destino de color
+    clear_depth: bool, // Primer pase que usa su destino de profundidad
+    resolve: bool,     // Con MSAA, último pase de la escena: resuelve y descarta las muestras
+}
+
+// Orden de ejecución por etapa y operaciones de carga y resolución de cada pase
+fn plan_passes<C>(passes: &[PassNode<C>], multisampled: bool) -> Vec<PassStep> {
+    let ordered: Vec<usize> = PassStage::ALL
+        .iter()
+        .flat_map(|&stage| (0..passes.len()).filter(move |&index| passes[index].stage == stage))
+        .collect();
+    // Solo el último pase de la escena resuelve el MSAA; las muestras ya no hacen falta después
+    let last_hdr_pass = ordered
+        .iter()
+        .rposition(|&index| passes[index].color == Some(ColorTarget::Hdr));
+
+    let mut written_colors: Vec<ColorTarget> = Vec::new();
+    let mut written_depths: Vec<DepthTarget> = Vec::new();
+    ordered
+        .into_iter()
+        .enumerate()
+        .map(|(position, index)| {
+            let pass = &passes[index];
+            let clear_color = pass.color.is_some_and(|target| {
+                let first = !written_colors.contains(&target);
+                if first {
+                    written_colors.push(target);
+                }
+                first
+            });
+            let clear_depth = pass.depth.is_some_and(|target| {
+                let first = !written_depths.contains(&target);
+                if first {
+                    written_depths.push(target);
+                }
+                first
+            });
+            PassStep {
+                pass: index,
+                clear_color,
+                clear_depth,
+                resolve: multisampled && last_hdr_pass == Some(position),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pass(name: &'static str, stage: PassStage, color: Option<ColorTarget>, depth: Option<DepthTarget>) -> PassNode<()> {
+        PassNode {
+            name,
+            stage,
+            color,
+            depth,
+            draw: draw_fn(|_: &(), _| {}),
+        }
+    }
+
+    // Pases como los de State, agregados fuera de orden a propósito
+    fn frame() -> Vec<PassNode<()>> {
+        vec![
+            pass("Tonemap", PassStage::PostProcess, Some(ColorTarget::Ldr), None),
+            pass("Planets", PassStage::Opaque, Some(ColorTarget::Hdr), Some(DepthTarget::Scene)),
+            pass("Shadow Map", PassStage::Shadow, None, Some(DepthTarget::Shadow)),
+            pass("Stars", PassStage::Background, Some(ColorTarget::Hdr), None),
+            pass("Atmospheres", PassStage::Transparent, Some(ColorTarget::Hdr), Some(DepthTarget::Scene)),
+            pass("Rings", PassStage::Opaque, Some(ColorTarget::Hdr), Some(DepthTarget::Scene)),
+            pass("FXAA", PassStage::PostProcess, Some(ColorTarget::Surface), None),
+        ]
+    }
+
+    fn names(passes: &[PassNode<()>], steps: &[PassStep]) -> Vec<&'static str> {
+        steps.iter().map(|step| passes[step.pass].name).collect()
+    }
+
+    #[test]
+    fn runs_by_stage_keeping_insertion_order_within_a_stage() {
+        let passes = frame();
+        let steps = plan_passes(&passes, false);
+        assert_eq!(
+            names(&passes, &steps),
+            ["Shadow Map", "Stars", "Planets", "Rings", "Atmospheres", "Tonemap", "FXAA"]
+        );
+    }
+
+    #[test]
+    fn first_write_to_a_target_clears_it() {
+        let passes = frame();
+        let steps = plan_passes(&passes, false);
+        let loads: Vec<(bool, bool)> = steps.iter().map(|step| (step.clear_color, step.clear_depth)).collect();
+        assert_eq!(
+            loads,
+            [
+                (false, true),  // Shadow Map: solo profundidad
+                (true, false),  // Stars: primera escritura en Hdr
+                (false, true),  // Planets: conserva Hdr, limpia la profundidad de la escena
+                (false, false), // Rings
+                (false, false), // Atmospheres
+                (true, false),  // Tonemap: primera escritura en Ldr
+                (true, false),  // FXAA: primera escritura en la swapchain
+            ]
+        );
+    }
+
+    #[test]
+    fn only_the_last_hdr_pass_resolves_with_msaa() {
+        let passes = frame();
+        assert!(plan_passes(&passes, false).iter().all(|step| !step.resolve));
+
+        let steps = plan_passes(&passes, true);
+        let resolving: Vec<&str> = steps
+            .iter()
+            .filter(|step| step.resolve)
+            .map(|step| passes[step.pass].name)
+            .collect();
+        assert_eq!(resolving, ["Atmospheres"]);
+    }
+}
@@ -0,0 +1,297 @@
+// Anillos planetarios: malla de corona circular, parámetros de la escena y pipeline translúcido
+
+use serde::Deserialize;
+use wgpu::util::DeviceExt;
+
+use crate::culling::BoundingSphere;
+use crate::scene::BodyParams;
+use crate::shaders;
+use crate::shadows::RingOccluder;
+use crate::Uniforms;
+
+const RING_SEGMENTS: usize = 256;
+
+// Anillos de un cuerpo, en su plano ecuatorial; los radios se expresan en radios del planeta
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RingParams {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub inner_color: [f32; 3],
+    pub outer_color: [f32; 3],
+    pub opacity: f32,
+    pub band_count: f32, // Cantidad aproximada de bandas de densidad
+    pub seed: u32,
+}
+
+impl Default for RingParams {
+    fn default() -> Self {
+        Self {
+            inner_radius: 1.3,
+            outer_radius: 2.3,
+            inner_color: [0.75, 0.68, 0.55],
+            outer_color: [0.9, 0.85, 0.72],
+            opacity: 0.85,
+            band_count: 12.0,
+            seed: 0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct RingUniforms {
+    inner_color: [f32; 4],
+    outer_color: [f32; 4],
+    inner_radius: f32,
+    outer_radius: f32,
+    opacity: f32,
+    band_count: f32,
+    seed: u32,
+    _padding: [u32; 3],
+}
+
+impl From<&RingParams> for RingUniforms {
+    fn from(params: &RingParams) -> Self {
+        let rgba = |[r, g, b]: [f32; 3]| [r, g, b, 1.0];
+        Self {
+            inner_color: rgba(params.inner_color),
+            outer_color: rgba(params.outer_color),
+            inner_radius: params.inner_radius,
+            outer_radius: params.outer_radius,
+            opacity: params.opacity,
+            band_count: params.band_count,
+            seed: params.seed,
+            _padding: [0; 3],
+        }
+    }
+}
+
+// Corona circular en el plano XZ entre `inner` y `outer`
+pub fn generate_annulus(inner: f32, outer: f32, segments: usize) -> (Vec<[f32; 3]>, Vec<u16>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    // El fragment shader recorta los bordes con el radio exacto; el polígono exterior
+    // se agranda para que sus lados no queden dentro del círculo
+    let outer = outer / (std::f32::consts::PI / segments as f32).cos();
+    for i in 0..=segments {
+        let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
+        let (sin, cos) = angle.sin_cos();
+        vertices.push([inner * cos, 0.0, inner * sin]);
+        vertices.push([outer * cos, 0.0, outer * sin]);
+    }
+
+    for i in 0..segments {
+        let inner_index = (i * 2) as u16;
+        let outer_index = inner_index + 1;
+        let next_inner = inner_index + 2;
+        let next_outer = inner_index + 3;
+
+        indices.extend_from_slice(&[inner_index, outer_index, next_inner]);
+        indices.extend_from_slice(&[outer_index, next_outer, next_inner]);
+    }
+
+    (vertices, indices)
+}
+
+// Pipeline compartido por los anillos de todos los cuerpos
+pub struct RingPipeline {
+    pub pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl RingPipeline {
+    // `shadow_layout` es el grupo 1 con las sombras del Sol
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        shadow_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Ring Bind Group Layout"),
+            entries: &[uniform_entry(0), uniform_entry(1)],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Ring Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("rings.wgsl", &[]).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Ring Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, shadow_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Ring Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<[f32; 3]>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None, // Los anillos se ven por ambas caras
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            // Se ocultan detrás de los planetas pero no tapan lo que se dibuja después
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+        }
+    }
+}
+
+pub struct Ring {
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+    uniforms: Uniforms,
+    tilt: cgmath::Matrix4<f32>,
+    scale: f32, // Radio del planeta
+    radius: f32, // Radio exterior en el mundo
+    pub center: cgmath::Vector3<f32>,
+    pub occluder: RingOccluder, // El anillo visto como un disco que tapa el Sol
+}
+
+impl Ring {
+    pub fn new(device: &wgpu::Device, pipeline: &RingPipeline, body: &BodyParams, params: &RingParams) -> Self {
+        let name = &body.name;
+        let uniforms = Uniforms::new([1.0, 1.0, 1.0, 1.0], body.scale, 0.0, 0.0);
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Ring Uniform Buffer", name)),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let ring_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Ring Buffer", name)),
+            contents: bytemuck::cast_slice(&[RingUniforms::from(params)]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Ring Bind Group"),
+            layout: &pipeline.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: ring_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let (vertices, indices) = generate_annulus(params.inner_radius, params.outer_radius, RING_SEGMENTS);
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ring Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ring Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            uniform_buffer,
+            bind_group,
+            vertex_buffer,
+            index_buffer,
+            num_indices: indices.len() as u32,
+            uniforms,
+            // Misma inclinación que el eje del planeta, fija en el espacio
+            tilt: cgmath::Matrix4::from_angle_z(cgmath::Deg(body.axial_tilt)),
+            scale: body.scale,
+            radius: body.scale * params.outer_radius,
+            center: cgmath::Vector3::new(0.0, 0.0, 0.0),
+            occluder: RingOccluder::new(
+                params.inner_radius * body.scale,
+                params.outer_radius * body.scale,
+                params.opacity,
+                params.band_count,
+                params.seed,
+            ),
+        }
+    }
+
+    // Sigue al planeta en `center`
+    pub fn update(&mut self, queue: &wgpu::Queue, view_proj: [[f32; 4]; 4], center: cgmath::Vector3<f32>, time: f32) {
+        self.center = center;
+        self.uniforms.view_proj = view_proj;
+        self.uniforms.time = time;
+        self.uniforms.model = (cgmath::Matrix4::from_translation(center)
+            * self.tilt
+            * cgmath::Matrix4::from_scale(self.scale))
+        .into();
+        self.occluder.place(center, (self.tilt * cgmath::Vector4::unit_y()).truncate());
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniforms]));
+    }
+
+    // Esfera que encierra el disco, para el frustum culling
+    pub fn bounds(&self) -> BoundingSphere {
+        BoundingSphere::new(self.center, self.radius)
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
@@ -0,0 +1,123 @@
+// Descripción de la escena cargada desde un archivo TOML (por defecto assets/scene.toml)
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::atmosphere::AtmosphereParams;
+use crate::belts::BeltParams;
+use crate::clouds::CloudParams;
+use crate::comets::CometParams;
+use crate::corona::CoronaParams;
+use crate::material::MaterialParams;
+use crate::orbits;
+use crate::rings::RingParams;
+use crate::terrain::TerrainParams;
+
+pub const DEFAULT_SCENE_PATH: &str = "assets/scene.toml";
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Scene {
+    pub background: BackgroundParams,
+    pub bodies: Vec<BodyParams>,
+    pub belts: Vec<BeltParams>,
+    pub comets: Vec<CometParams>,
+}
+
+// Un cuerpo del sistema: tamaño, órbita alrededor de su padre (o del origen) y material
+#[derive(Clone, Debug, Deserialize)]
+pub struct BodyParams {
+    pub name: String,
+    #[serde(default)]
+    pub parent: Option<String>, // Nombre del cuerpo alrededor del cual orbita
+    pub scale: f32,
+    #[serde(default)]
+    pub orbital_radius: f32,
+    #[serde(default)]
+    pub orbital_speed: f32,
+    #[serde(default)]
+    pub eccentricity: f32, // 0 = órbita circular
+    #[serde(default)]
+    pub inclination: f32, // Grados respecto al plano orbital del padre
+    #[serde(default)]
+    pub periapsis: f32, // Dirección del punto más cercano al padre (grados)
+    #[serde(default)]
+    pub rotation_period: f32, // Segundos por vuelta sobre su eje; 0 = sin giro
+    #[serde(default)]
+    pub axial_tilt: f32, // Grados; más de 90 indica giro retrógrado (Venus)
+    #[serde(default = "default_body_color")]
+    pub color: [f32; 3], // Color identificativo del cuerpo
+    #[serde(default)]
+    pub material: MaterialParams,
+    #[serde(default)]
+    pub rings: Option<RingParams>,
+    #[serde(default)]
+    pub corona: Option<CoronaParams>, // Halo de las estrellas
+    #[serde(default)]
+    pub atmosphere: Option<AtmosphereParams>,
+    #[serde(default)]
+    pub clouds: Option<CloudParams>, // Capa de nubes con su propia rotación
+    #[serde(default)]
+    pub terrain: Option<TerrainParams>, // Relieve de la superficie al acercarse
+}
+
+fn default_body_color() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+// Parámetros del cielo procedural: banda galáctica de estrellas y nebulosas
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BackgroundParams {
+    pub seed: u32,
+    pub band_fraction: f32,       // Fracción de estrellas que cae dentro de la banda galáctica
+    pub band_width: f32,          // Desviación angular de la banda (grados)
+    pub galactic_tilt: f32,       // Inclinación de la banda respecto al plano orbital (grados)
+    pub core_longitude: f32,      // Dirección del núcleo galáctico dentro de la banda (grados)
+    pub nebula_resolution: u32,   // Tamaño de cada cara del cubemap horneado
+    pub nebula_density: f32,      // Cobertura de las nubes (0 = ninguna, 1 = todo el cielo)
+    pub nebula_scale: f32,        // Frecuencia del ruido de las nubes
+    pub nebula_brightness: f32,
+    pub nebula_colors: [[f32; 3]; 3],
+}
+
+impl Default for BackgroundParams {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            band_fraction: 0.6,
+            band_width: 8.0,
+            galactic_tilt: 60.0,
+            core_longitude: 0.0,
+            nebula_resolution: 512,
+            nebula_density: 0.5,
+            nebula_scale: 2.5,
+            nebula_brightness: 0.35,
+            nebula_colors: [
+                [0.45, 0.20, 0.55], // Violeta
+                [0.15, 0.30, 0.65], // Azul
+                [0.70, 0.25, 0.25], // Rojo de hidrógeno
+            ],
+        }
+    }
+}
+
+impl Scene {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|error| format!("No se pudo leer la escena {}: {}", path.display(), error))?;
+        let scene: Self =
+            toml::from_str(&source).map_err(|error| format!("Escena inválida en {}: {}", path.display(), error))?;
+        for body in &scene.bodies {
+            orbits::validate_eccentricity(&body.name, body.eccentricity)?;
+        }
+        for comet in &scene.comets {
+            orbits::validate_eccentricity(&comet.name, comet.eccentricity)?;
+        }
+        for belt in &scene.belts {
+            belt.validate()?;
+        }
+        Ok(scene)
+    }
+}
@@ -0,0 +1,153 @@
+// Jerarquía de transformaciones: cada cuerpo orbita alrededor de su padre (o del origen)
+
+use cgmath::{Deg, Matrix4, Rad, Transform, Vector3};
+
+use crate::orbits::Orbit;
+use crate::scene::BodyParams;
+
+pub struct SceneNode {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub scale: f32,
+    pub orbit: Orbit,
+    rotation_period: f32,
+    tilt: Matrix4<f32>,         // Inclinación del eje, fija en el espacio
+    pub position: Vector3<f32>, // Posición en el mundo, calculada en `update`
+    pub axis: Vector3<f32>,     // Eje de rotación (polo norte) en el mundo
+    pub model: Matrix4<f32>,
+}
+
+pub struct SceneGraph {
+    nodes: Vec<SceneNode>,
+    order: Vec<usize>, // Orden de actualización: los padres antes que sus hijos
+}
+
+impl SceneGraph {
+    // Un nodo por cuerpo, en el mismo orden que en la escena
+    pub fn new(bodies: &[BodyParams]) -> Result<Self, String> {
+        let mut nodes = Vec::with_capacity(bodies.len());
+        for body in bodies {
+            let parent = match &body.parent {
+                Some(parent) => Some(
+                    bodies
+                        .iter()
+                        .position(|other| other.name == *parent)
+                        .ok_or_else(|| format!("{}: cuerpo padre desconocido \"{}\"", body.name, parent))?,
+                ),
+                None => None,
+            };
+            let tilt = Matrix4::from_angle_z(Deg(body.axial_tilt));
+            nodes.push(SceneNode {
+                name: body.name.clone(),
+                parent,
+                scale: body.scale,
+                orbit: Orbit::from_body(body),
+                rotation_period: body.rotation_period,
+                tilt,
+                position: Vector3::new(0.0, 0.0, 0.0),
+                axis: tilt.transform_vector(Vector3::unit_y()),
+                model: Matrix4::from_scale(body.scale),
+            });
+        }
+
+        // Profundidad de cada nodo; una cadena más larga que la escena indica un ciclo
+        let mut depths = Vec::with_capacity(nodes.len());
+        for (index, node) in nodes.iter().enumerate() {
+            let mut depth = 0;
+            let mut ancestor = node.parent;
+            while let Some(parent) = ancestor {
+                depth += 1;
+                if depth > nodes.len() {
+                    return Err(format!("{}: la jerarquía de cuerpos forma un ciclo", nodes[index].name));
+                }
+                ancestor = nodes[parent].parent;
+            }
+            depths.push(depth);
+        }
+        let mut order: Vec<usize> = (0..nodes.len()).collect();
+        order.sort_by_key(|&index| depths[index]);
+
+        Ok(Self { nodes, order })
+    }
+
+    pub fn nodes(&self) -> &[SceneNode] {
+        &self.nodes
+    }
+
+    // Cuerpos sin padre, en el orden de la escena
+    pub fn roots(&self) -> Vec<usize> {
+        (0..self.nodes.len()).filter(|&index| self.nodes[index].parent.is_none()).collect()
+    }
+
+    // Posiciones en el instante `time`. Los hijos heredan solo la posición del padre,
+    // no su giro ni su escala. El giro propio es independiente de la órbita.
+    pub fn update(&mut self, time: f32) {
+        for &index in &self.order {
+            let origin = match self.nodes[index].parent {
+                Some(parent) => self.nodes[parent].position,
+                None => Vector3::new(0.0, 0.0, 0.0),
+            };
+
+            let node = &mut self.nodes[index];
+            node.position = origin + node.orbit.position(time);
+
+            let spin = if node.rotation_period != 0.0 {
+                time / node.rotation_period * std::f32::consts::TAU
+            } else {
+                0.0
+            };
+            node.model = Matrix4::from_translation(node.position)
+                * node.tilt
+                * Matrix4::from_angle_y(Rad(spin))
+                * Matrix4::from_scale(node.scale);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::InnerSpace;
+
+    fn body(name: &str, parent: Option<&str>, orbital_radius: f32) -> BodyParams {
+        let parent = parent.map_or(String::new(), |parent| format!("parent = \"{}\"\n", parent));
+        toml::from_str(&format!(
+            "name = \"{}\"\n{}scale = 1.0\norbital_radius = {}\norbital_speed = 0.5\n[material]\nkind = \"rocky\"",
+            name, parent, orbital_radius
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn moons_follow_their_parent() {
+        let bodies = [body("Tierra", None, 10.0), body("Luna", Some("Tierra"), 2.0)];
+        let mut graph = SceneGraph::new(&bodies).unwrap();
+        graph.update(1.3);
+        let [earth, moon] = [&graph.nodes()[0], &graph.nodes()[1]];
+        assert_eq!(moon.parent, Some(0));
+        assert!((moon.position - earth.position - moon.orbit.position(1.3)).magnitude() < 1e-5);
+        assert!(((moon.position - earth.position).magnitude() - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn children_listed_first_update_after_their_parent() {
+        let bodies = [body("Luna", Some("Tierra"), 2.0), body("Tierra", None, 10.0)];
+        let mut graph = SceneGraph::new(&bodies).unwrap();
+        assert_eq!(graph.order, [1, 0]);
+        graph.update(2.0);
+        let [moon, earth] = [&graph.nodes()[0], &graph.nodes()[1]];
+        assert!(((moon.position - earth.position).magnitude() - 2.0).abs() < 1e-4);
+        assert!((earth.position.magnitude() - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rejects_cycles_and_unknown_parents() {
+        let cycle = [body("A", Some("B"), 1.0), body("B", Some("A"), 1.0)];
+        let error = SceneGraph::new(&cycle).err().unwrap();
+        assert!(error.contains("ciclo"), "{}", error);
+
+        let orphan = [body("Luna", Some("Tierra"), 2.0)];
+        let error = SceneGraph::new(&orphan).err().unwrap();
+        assert!(error.starts_with("Luna: cuerpo padre desconocido"), "{}", error);
+    }
+}
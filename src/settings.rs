@@ -0,0 +1,148 @@
+// Opciones de ejecución leídas desde la línea de comandos.
+// Ejemplo: cargo run -- --stars=cpu --star-count=500000 --star-seed=7 --exposure=1.5
+
+use std::path::PathBuf;
+
+use crate::eclipses::EclipseQuery;
+use crate::scene::DEFAULT_SCENE_PATH;
+
+// Cantidad de estrellas por defecto
+pub const STAR_COUNT: u32 = 2_000_000;
+
+// Dónde se generan las posiciones de las estrellas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StarGeneration {
+    Gpu, // Se calculan en el vertex shader a partir del índice y la semilla
+    Cpu, // Respaldo: se calculan en la CPU y se suben en un vertex buffer
+}
+
+// Qué se dibuja detrás de los planetas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Background {
+    Stars,  // Solo el campo de estrellas puntuales
+    Skybox, // Solo el cubemap (nebulosa procedural o imagen de --skybox)
+    Both,   // Cubemap con las estrellas puntuales encima
+}
+
+impl Background {
+    pub fn has_stars(self) -> bool {
+        self != Background::Skybox
+    }
+
+    pub fn has_skybox(self) -> bool {
+        self != Background::Stars
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub star_generation: StarGeneration,
+    pub star_count: u32,
+    pub star_seed: Option<u32>, // Reemplaza la semilla del fondo definida en la escena
+    pub background: Background,
+    pub skybox_path: Option<PathBuf>, // Carpeta con seis caras o imagen equirectangular
+    pub scene_path: PathBuf,
+    pub exposure: f32,        // Multiplica la luz de la escena antes del tonemapping
+    pub bloom: bool,
+    pub bloom_intensity: f32, // Peso del bloom al combinarlo con la escena
+    pub msaa_samples: u32,    // Muestras por píxel de los pases de la escena (1 = sin MSAA)
+    pub fxaa: bool,           // Antialiasing FXAA sobre la imagen ya tonemapeada
+    pub eclipses: Option<EclipseQuery>, // Predicción de eclipses en lugar de (o antes de) la ventana
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            star_generation: StarGeneration::Gpu,
+            star_count: STAR_COUNT,
+            star_seed: None,
+            background: Background::Both,
+            skybox_path: None,
+            scene_path: PathBuf::from(DEFAULT_SCENE_PATH),
+            exposure: 1.0,
+            bloom: true,
+            bloom_intensity: 0.15,
+            msaa_samples: 4,
+            fxaa: false,
+            eclipses: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_args() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    fn parse(args: impl Iterator<Item = String>) -> Self {
+        let mut settings = Self::default();
+        let mut background = None;
+        let mut eclipses = EclipseQuery::default();
+
+        for arg in args {
+            let (key, value) = arg.split_once('=').unwrap_or((arg.as_str(), ""));
+            match (key, value) {
+                ("--stars", "gpu") => settings.star_generation = StarGeneration::Gpu,
+                ("--stars", "cpu") => settings.star_generation = StarGeneration::Cpu,
+                ("--star-count", value) => match value.parse() {
+                    Ok(count) => settings.star_count = count,
+                    Err(_) => eprintln!("Valor inválido para --star-count: {}", value),
+                },
+                ("--star-seed", value) => match value.parse() {
+                    Ok(seed) => settings.star_seed = Some(seed),
+                    Err(_) => eprintln!("Valor inválido para --star-seed: {}", value),
+                },
+                ("--background", "stars") => background = Some(Background::Stars),
+                ("--background", "skybox") => background = Some(Background::Skybox),
+                ("--background", "both") => background = Some(Background::Both),
+                ("--skybox", path) if !path.is_empty() => settings.skybox_path = Some(PathBuf::from(path)),
+                ("--scene", path) if !path.is_empty() => settings.scene_path = PathBuf::from(path),
+                ("--exposure", value) => match value.parse() {
+                    Ok(exposure) => settings.exposure = exposure,
+                    Err(_) => eprintln!("Valor inválido para --exposure: {}", value),
+                },
+                ("--bloom", "on") => settings.bloom = true,
+                ("--bloom", "off") => settings.bloom = false,
+                ("--bloom-intensity", value) => match value.parse() {
+                    Ok(intensity) => settings.bloom_intensity = intensity,
+                    Err(_) => eprintln!("Valor inválido para --bloom-intensity: {}", value),
+                },
+                ("--msaa", value) => match value.parse() {
+                    Ok(samples @ (1 | 2 | 4 | 8)) => settings.msaa_samples = samples,
+                    _ => eprintln!("Valor inválido para --msaa: {} (1, 2, 4 u 8)", value),
+                },
+                ("--fxaa", "on") => settings.fxaa = true,
+                ("--fxaa", "off") => settings.fxaa = false,
+                ("--eclipses", name) if !name.is_empty() => eclipses.observer = name.to_string(),
+                ("--eclipse-from", value) => match value.parse() {
+                    Ok(start) => eclipses.start = start,
+                    Err(_) => eprintln!("Valor inválido para --eclipse-from: {}", value),
+                },
+                ("--eclipse-to", value) => match value.parse() {
+                    Ok(end) => eclipses.end = end,
+                    Err(_) => eprintln!("Valor inválido para --eclipse-to: {}", value),
+                },
+                ("--eclipse-step", value) => match value.parse() {
+                    Ok(step) => eclipses.step = step,
+                    Err(_) => eprintln!("Valor inválido para --eclipse-step: {}", value),
+                },
+                ("--jump-to-event", value) => match value.parse() {
+                    Ok(number) => eclipses.jump_to = Some(number),
+                    Err(_) => eprintln!("Valor inválido para --jump-to-event: {}", value),
+                },
+                _ => eprintln!("Opción desconocida: {}", arg),
+            }
+        }
+
+        if let Some(background) = background {
+            settings.background = background;
+        }
+        if !eclipses.observer.is_empty() {
+            settings.eclipses = Some(eclipses);
+        } else if eclipses.jump_to.is_some() {
+            eprintln!("--jump-to-event necesita --eclipses=CUERPO");
+        }
+
+        settings
+    }
+}
@@ -0,0 +1,125 @@
+// Atmósfera: dispersión simple de Rayleigh y Mie integrada a lo largo del rayo de la cámara.
+// Los cálculos se hacen en radios del planeta, con el planeta centrado en el origen.
+
+#include "common.wgsl"
+#include "shadows.wgsl"
+
+// Debe coincidir con `atmosphere::AtmosphereUniforms`
+struct Atmosphere {
+    rayleigh: vec4<f32>, // Coeficientes y altura de escala
+    height: f32,         // Radio de la capa exterior
+    mie: f32,
+    mie_height: f32,
+    mie_g: f32,
+    sun_intensity: f32,
+};
+
+@group(0) @binding(1) var<uniform> atmosphere: Atmosphere;
+
+const VIEW_SAMPLES: i32 = 12;
+const LIGHT_SAMPLES: i32 = 4;
+const PI: f32 = 3.1415927;
+// Las partículas de Mie también absorben una parte de la luz
+const MIE_EXTINCTION: f32 = 1.1;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> VertexOutput {
+    let world = uniforms.model * vec4<f32>(position, 1.0);
+
+    var out: VertexOutput;
+    out.clip_position = uniforms.view_proj * world;
+    out.world_position = world.xyz;
+    return out;
+}
+
+// Distancias de entrada y salida del rayo en una esfera centrada en el origen (x > y si no la toca)
+fn ray_sphere(origin: vec3<f32>, direction: vec3<f32>, radius: f32) -> vec2<f32> {
+    let b = dot(origin, direction);
+    let c = dot(origin, origin) - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return vec2<f32>(1e9, -1e9);
+    }
+    let s = sqrt(discriminant);
+    return vec2<f32>(-b - s, -b + s);
+}
+
+// Densidad relativa de Rayleigh (x) y de Mie (y), que decae exponencialmente con la altura
+fn density(point: vec3<f32>) -> vec2<f32> {
+    let altitude = max(length(point) - 1.0, 0.0);
+    return exp(-altitude / vec2<f32>(atmosphere.rayleigh.w, atmosphere.mie_height));
+}
+
+// Profundidad óptica desde `point` hacia el Sol; muy grande si el planeta lo tapa
+fn light_depth(point: vec3<f32>, light_dir: vec3<f32>) -> vec2<f32> {
+    let ground = ray_sphere(point, light_dir, 1.0);
+    if ground.x > 0.0 && ground.x < ground.y {
+        return vec2<f32>(1e4);
+    }
+    let step = ray_sphere(point, light_dir, atmosphere.height).y / f32(LIGHT_SAMPLES);
+    var depth = vec2<f32>(0.0);
+    for (var i = 0; i < LIGHT_SAMPLES; i++) {
+        depth += density(point + light_dir * (f32(i) + 0.5) * step) * step;
+    }
+    return depth;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let center = uniforms.model[3].xyz;
+    let planet_radius = length(uniforms.model[0].xyz) / atmosphere.height;
+    let camera = (uniforms.camera_position.xyz - center) / planet_radius;
+    let direction = normalize(in.world_position - uniforms.camera_position.xyz);
+
+    // Tramo del rayo dentro de la capa, cortado por la superficie del planeta
+    let shell = ray_sphere(camera, direction, atmosphere.height);
+    let start = max(shell.x, 0.0);
+    var end = shell.y;
+    let ground = ray_sphere(camera, direction, 1.0);
+    if ground.x > 0.0 && ground.x < ground.y {
+        end = min(end, ground.x);
+    }
+    if end <= start {
+        discard;
+    }
+
+    // El Sol está lejos comparado con el planeta: una sola dirección de luz para toda la capa
+    let light_dir = sun_direction(center);
+    let beta_rayleigh = atmosphere.rayleigh.rgb;
+    let beta_mie = vec3<f32>(atmosphere.mie);
+
+    let step = (end - start) / f32(VIEW_SAMPLES);
+    var rayleigh_sum = vec3<f32>(0.0);
+    var mie_sum = vec3<f32>(0.0);
+    var view_depth = vec2<f32>(0.0);
+    for (var i = 0; i < VIEW_SAMPLES; i++) {
+        let point = camera + direction * (start + (f32(i) + 0.5) * step);
+        let local_density = density(point) * step;
+        view_depth += local_density;
+
+        let depth = view_depth + light_depth(point, light_dir);
+        let attenuation = exp(-(beta_rayleigh * depth.x + beta_mie * MIE_EXTINCTION * depth.y));
+        rayleigh_sum += local_density.x * attenuation;
+        mie_sum += local_density.y * attenuation;
+    }
+
+    // Funciones de fase: Rayleigh casi simétrica, Mie concentrada hacia el Sol (Cornette-Shanks)
+    let mu = dot(direction, light_dir);
+    let g = atmosphere.mie_g;
+    let phase_rayleigh = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+    let phase_mie = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+        / ((2.0 + g * g) * pow(1.0 + g * g - 2.0 * g * mu, 1.5));
+
+    let color = atmosphere.sun_intensity
+        * (rayleigh_sum * beta_rayleigh * phase_rayleigh + mie_sum * beta_mie * phase_mie);
+
+    // Lo que hay detrás (la superficie o el espacio) se atenúa según la transmitancia media
+    let transmittance = exp(-(beta_rayleigh * view_depth.x + beta_mie * MIE_EXTINCTION * view_depth.y));
+    let alpha = 1.0 - dot(transmittance, vec3<f32>(1.0 / 3.0));
+    return vec4<f32>(color, alpha);
+}
@@ -0,0 +1,105 @@
+// Cinturones de asteroides: cada instancia es una roca con su propia órbita y giro,
+// calculados aquí a partir de los parámetros fijos de la instancia y del tiempo.
+
+#include "lighting.wgsl"
+#include "shadows.wgsl"
+
+// Debe coincidir con `belts::BeltUniforms`
+struct BeltUniforms {
+    view_proj: mat4x4<f32>,
+    colors: array<vec4<f32>, 2>,
+    frustum: array<vec4<f32>, 6>, // Planos de la vista con la normal hacia adentro
+    time: f32,
+    ambient: f32,
+};
+
+@group(0) @binding(0) var<uniform> belt: BeltUniforms;
+
+// Radio que encierra cualquier roca de tamaño 1; debe coincidir con `belts::ROCK_RADIUS`
+const ROCK_RADIUS: f32 = 1.6;
+
+// Debe coincidir con `belts::RockInstance`
+struct RockInstance {
+    @location(2) orbit: vec4<f32>,       // Semieje mayor, excentricidad, anomalía inicial, velocidad
+    @location(3) orientation: vec4<f32>, // Inclinación, nodo ascendente, tamaño, velocidad de giro
+    @location(4) tumble: vec4<f32>,      // Eje de giro (xyz) y mezcla de color (w)
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_normal: vec3<f32>,
+    @location(1) world_position: vec3<f32>,
+    @location(2) color: vec3<f32>,
+};
+
+// Rotación de `v` alrededor del eje unitario `axis` (fórmula de Rodrigues)
+fn rotate(v: vec3<f32>, axis: vec3<f32>, angle: f32) -> vec3<f32> {
+    let c = cos(angle);
+    let s = sin(angle);
+    return v * c + cross(axis, v) * s + axis * dot(axis, v) * (1.0 - c);
+}
+
+fn orbit_position(orbit: vec4<f32>, inclination: f32, node: f32) -> vec3<f32> {
+    let a = orbit.x;
+    let e = orbit.y;
+    let mean_anomaly = orbit.z + belt.time * orbit.w;
+
+    // Dos iteraciones de la ecuación de Kepler bastan para excentricidades pequeñas
+    var anomaly = mean_anomaly + e * sin(mean_anomaly);
+    anomaly = mean_anomaly + e * sin(anomaly);
+
+    let b = a * sqrt(1.0 - e * e);
+    let planar = vec3<f32>(a * (cos(anomaly) - e), 0.0, b * sin(anomaly));
+
+    // Inclinación alrededor de X y luego orientación del nodo alrededor de Y
+    let inclined = vec3<f32>(planar.x, -planar.z * sin(inclination), planar.z * cos(inclination));
+    return vec3<f32>(
+        inclined.x * cos(node) + inclined.z * sin(node),
+        inclined.y,
+        -inclined.x * sin(node) + inclined.z * cos(node),
+    );
+}
+
+// La esfera de la roca toca la pirámide de visión
+fn in_frustum(center: vec3<f32>, radius: f32) -> bool {
+    for (var i = 0; i < 6; i++) {
+        let plane = belt.frustum[i];
+        if dot(plane.xyz, center) + plane.w < -radius {
+            return false;
+        }
+    }
+    return true;
+}
+
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    instance: RockInstance,
+) -> VertexOutput {
+    let center = orbit_position(instance.orbit, instance.orientation.x, instance.orientation.y);
+
+    // Roca fuera de la vista: todos sus vértices van al mismo punto fuera del volumen de
+    // recorte y la GPU descarta sus triángulos sin rasterizarlos
+    var out: VertexOutput;
+    if !in_frustum(center, instance.orientation.z * ROCK_RADIUS) {
+        out.clip_position = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+        return out;
+    }
+
+    let spin = instance.orbit.z + belt.time * instance.orientation.w;
+    let axis = normalize(instance.tumble.xyz);
+    let world = center + rotate(position * instance.orientation.z, axis, spin);
+
+    out.clip_position = belt.view_proj * vec4<f32>(world, 1.0);
+    out.world_normal = rotate(normal, axis, spin);
+    out.world_position = world;
+    out.color = mix(belt.colors[0].rgb, belt.colors[1].rgb, instance.tumble.w);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let diffuse = lambert(in.world_normal, sun_direction(in.world_position));
+    return vec4<f32>((belt.ambient + diffuse) * in.color, 1.0);
+}
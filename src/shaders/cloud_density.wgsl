@@ -0,0 +1,10 @@
+// Densidad de la capa de nubes, compartida por la capa y por la sombra sobre el suelo
+
+#include "noise.wgsl"
+
+// Densidad en [0, 1] en la dirección `p`, expresada en el marco que gira con la capa
+fn cloud_density(p: vec3<f32>, seed: vec3<f32>, coverage: f32, time: f32) -> f32 {
+    let drift = vec3<f32>(time * 0.02, 0.0, time * 0.01);
+    let clouds = fbm3(domain_warp3(p * 3.0 + seed.zxy + drift, 0.6), 5) * 0.5 + 0.5;
+    return smoothstep(1.0 - coverage, 1.0 - coverage * 0.5, clouds);
+}
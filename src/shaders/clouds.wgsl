@@ -0,0 +1,54 @@
+// Capa de nubes: una esfera algo mayor que el planeta que gira a su propio ritmo
+
+#include "common.wgsl"
+#include "lighting.wgsl"
+#include "cloud_density.wgsl"
+#include "shadows.wgsl"
+
+// Debe coincidir con `clouds::CloudUniforms`
+struct Clouds {
+    color: vec4<f32>,
+    coverage: f32,
+    opacity: f32,
+    seed: u32,
+};
+
+@group(0) @binding(1) var<uniform> clouds: Clouds;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) local_position: vec3<f32>, // Gira junto con la capa
+    @location(1) world_normal: vec3<f32>,
+    @location(2) world_position: vec3<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> VertexOutput {
+    let world = uniforms.model * vec4<f32>(position, 1.0);
+
+    var out: VertexOutput;
+    out.clip_position = uniforms.view_proj * world;
+    out.local_position = position;
+    out.world_normal = normalize((uniforms.model * vec4<f32>(position, 0.0)).xyz);
+    out.world_position = world.xyz;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let p = normalize(in.local_position);
+    let density = cloud_density(p, seed_offset(clouds.seed), clouds.coverage, uniforms.time);
+    if density <= 0.001 {
+        discard;
+    }
+
+    // Terminador suave: las nubes altas siguen iluminadas un poco después de la puesta
+    let light_dir = sun_direction(in.world_position);
+    let light = smoothstep(-0.15, 0.35, dot(normalize(in.world_normal), light_dir)) * sun_visibility(in.world_position);
+    let color = clouds.color.rgb * (0.04 + light);
+
+    // Las nubes más densas del borde se ven más opacas por el recorrido oblicuo
+    let view_cos = abs(dot(normalize(in.world_normal), normalize(uniforms.camera_position.xyz - in.world_position)));
+    let alpha = clamp(density * clouds.opacity * (1.0 + 0.5 * (1.0 - view_cos)), 0.0, 1.0);
+    return vec4<f32>(color, alpha);
+}
@@ -0,0 +1,100 @@
+// Cometas: la coma y las partículas de las colas son billboards con mezcla aditiva.
+// Cada partícula avanza por su cola de forma cíclica según el tiempo y su desfase.
+
+// Debe coincidir con `comets::CometUniforms`
+struct CometUniforms {
+    view_proj: mat4x4<f32>,
+    camera_right: vec4<f32>,
+    camera_up: vec4<f32>,
+    nucleus: vec4<f32>,  // Posición y radio de la coma
+    anti_sun: vec4<f32>, // Dirección opuesta al Sol y longitud de las colas
+    wake: vec4<f32>,     // Dirección opuesta a la velocidad y actividad (0..1)
+    ion_color: vec4<f32>,
+    dust_color: vec4<f32>,
+    time: f32,
+};
+
+@group(0) @binding(0) var<uniform> comet: CometUniforms;
+
+// Deben coincidir con `comets::PARTICLE_*`
+const PARTICLE_COMA: u32 = 0u;
+const PARTICLE_ION: u32 = 1u;
+
+// Debe coincidir con `comets::ParticleInstance`
+struct Particle {
+    @location(0) kind: u32,
+    @location(1) phase: f32,
+    @location(2) offset: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+// Dos ejes perpendiculares a `direction` para repartir las partículas alrededor de la cola
+fn tail_basis(direction: vec3<f32>) -> mat2x3<f32> {
+    var reference = vec3<f32>(0.0, 1.0, 0.0);
+    if abs(direction.y) > 0.9 {
+        reference = vec3<f32>(1.0, 0.0, 0.0);
+    }
+    let side = normalize(cross(direction, reference));
+    return mat2x3<f32>(side, cross(direction, side));
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, particle: Particle) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+
+    let activity = comet.wake.w;
+    let tail_length = comet.anti_sun.w;
+    let anti_sun = comet.anti_sun.xyz;
+    let basis = tail_basis(anti_sun);
+
+    var center = comet.nucleus.xyz;
+    var size = 0.0;
+    var color = vec3<f32>(0.0);
+
+    if particle.kind == PARTICLE_COMA {
+        size = comet.nucleus.w * 2.5;
+        color = mix(comet.dust_color.rgb, vec3<f32>(1.0), 0.5) * (0.3 + activity);
+    } else if particle.kind == PARTICLE_ION {
+        // Los iones salen rápido en línea recta, empujados por el viento solar
+        let t = fract(particle.phase + comet.time * 0.6);
+        let spread = 0.04 * tail_length * (0.3 + t);
+        center += anti_sun * t * tail_length + basis * (particle.offset * spread);
+        size = 0.04 + 0.06 * t;
+        color = comet.ion_color.rgb * (1.0 - t) * 0.35 * activity;
+    } else {
+        // El polvo es más lento y se queda atrás en la órbita: la cola se curva
+        let t = fract(particle.phase + comet.time * 0.15);
+        let spread = 0.12 * tail_length * (0.2 + t);
+        center += (anti_sun * t + comet.wake.xyz * t * t * 0.6) * tail_length + basis * (particle.offset * spread);
+        size = 0.06 + 0.12 * t;
+        color = comet.dust_color.rgb * (1.0 - t) * 0.25 * activity;
+    }
+
+    let world = center + (comet.camera_right.xyz * corner.x + comet.camera_up.xyz * corner.y) * size;
+
+    var out: VertexOutput;
+    out.clip_position = comet.view_proj * vec4<f32>(world, 1.0);
+    out.uv = corner;
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Brillo gaussiano dentro del quad
+    let falloff = exp(-4.0 * dot(in.uv, in.uv));
+    return vec4<f32>(in.color * falloff, 1.0);
+}
@@ -0,0 +1,15 @@
+// Uniforms por objeto compartidos por los planetas y la nave.
+// Debe coincidir con `Uniforms` en main.rs.
+
+struct Uniforms {
+    view_proj: mat4x4<f32>,
+    model: mat4x4<f32>,
+    color: vec4<f32>,
+    time: f32,
+    orbital_radius: f32,
+    orbital_speed: f32,
+    _padding: f32,
+    camera_position: vec4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
@@ -0,0 +1,102 @@
+// Corona de una estrella: halo con rayos que fluyen hacia afuera y protuberancias en
+// forma de arco que nacen, crecen y se apagan en posiciones al azar del limbo.
+
+#include "noise.wgsl"
+
+// Debe coincidir con `corona::CoronaUniforms`
+struct CoronaUniforms {
+    view_proj: mat4x4<f32>,
+    camera_right: vec4<f32>,
+    camera_up: vec4<f32>,
+    center: vec4<f32>,           // Centro y radio de la estrella
+    color: vec4<f32>,            // Color e intensidad del halo
+    prominence_color: vec4<f32>, // Color y altura máxima de las protuberancias
+    extent: f32,                 // Radio del billboard, en radios de la estrella
+    streamers: f32,
+    prominences: u32,
+    seed: u32,
+    time: f32,
+};
+
+@group(0) @binding(0) var<uniform> corona: CoronaUniforms;
+
+const TAU: f32 = 6.2831853;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) offset: vec2<f32>, // Posición en el plano del billboard, en radios de la estrella
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    let offset = corners[vertex_index] * corona.extent;
+    let world = corona.center.xyz
+        + (corona.camera_right.xyz * offset.x + corona.camera_up.xyz * offset.y) * corona.center.w;
+
+    var out: VertexOutput;
+    out.clip_position = corona.view_proj * vec4<f32>(world, 1.0);
+    out.offset = offset;
+    return out;
+}
+
+// Una protuberancia: un arco sobre el limbo que vive un ciclo completo y a veces no aparece
+fn prominence(index: u32, radius: f32, angle: f32, seed: vec3<f32>) -> f32 {
+    let slot = hash33(seed + vec3<f32>(f32(index), 0.0, 0.0));
+    let period = 12.0 + slot.x * 18.0;
+    let phase = corona.time / period + slot.y;
+    let cycle = floor(phase);
+    let life = fract(phase);
+
+    let event = hash33(seed + vec3<f32>(f32(index), cycle, 1.0));
+    if event.x < 0.4 {
+        return 0.0; // Ciclo sin protuberancia
+    }
+
+    // Posición y ancho angular del arco; la altura sube y baja durante el ciclo
+    let center = event.y * TAU;
+    let width = 0.08 + event.z * 0.12;
+    let difference = atan2(sin(angle - center), cos(angle - center)) / width;
+    if abs(difference) >= 1.0 {
+        return 0.0;
+    }
+    let envelope = sin(life * 3.1415927);
+    let height = sqrt(1.0 - difference * difference) * corona.prominence_color.a * envelope;
+
+    // Grosor del arco con filamentos de ruido
+    let thickness = 0.03 + 0.02 * envelope;
+    let arc = exp(-pow((radius - 1.0 - height) / thickness, 2.0));
+    let filaments = 0.6 + 0.4 * simplex3(vec3<f32>(difference * 4.0, radius * 12.0, corona.time * 0.3) + seed);
+    return arc * filaments * envelope;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let radius = length(in.offset);
+    if radius < 1.0 || radius > corona.extent {
+        discard;
+    }
+    let angle = atan2(in.offset.y, in.offset.x);
+    let direction = in.offset / radius;
+    let seed = seed_offset(corona.seed);
+
+    // Halo que decae con la altura y rayos que se desplazan lentamente hacia afuera
+    let height = radius - 1.0;
+    let glow = exp(-height * 3.0) + 0.3 * exp(-height * 0.8);
+    let rays = fbm3(vec3<f32>(direction * corona.streamers * 0.5, height * 0.6 - corona.time * 0.03) + seed, 4);
+    let streamers = 0.55 + 0.9 * max(rays, -0.5);
+    let fade = 1.0 - smoothstep(corona.extent * 0.6, corona.extent, radius);
+    var color = corona.color.rgb * corona.color.a * glow * streamers * fade;
+
+    for (var i = 0u; i < corona.prominences; i++) {
+        color += corona.prominence_color.rgb * prominence(i, radius, angle, seed);
+    }
+    return vec4<f32>(color, 1.0);
+}
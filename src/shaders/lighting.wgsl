@@ -0,0 +1,15 @@
+// Iluminación difusa y especular compartida por los shaders de superficie
+
+fn lambert(normal: vec3<f32>, light_dir: vec3<f32>) -> f32 {
+    return max(dot(normalize(normal), light_dir), 0.0);
+}
+
+// Reflejo de Blinn-Phong; solo en la cara iluminada
+fn blinn_phong(normal: vec3<f32>, light_dir: vec3<f32>, view_dir: vec3<f32>, shininess: f32) -> f32 {
+    let n = normalize(normal);
+    if dot(n, light_dir) <= 0.0 {
+        return 0.0;
+    }
+    let halfway = normalize(light_dir + view_dir);
+    return pow(max(dot(n, halfway), 0.0), shininess);
+}
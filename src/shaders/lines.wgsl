@@ -0,0 +1,25 @@
+// Líneas de ayuda (ejes, órbitas): color por vértice, sin iluminación
+
+struct LineUniforms {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> lines: LineUniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = lines.view_proj * vec4<f32>(position, 1.0);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
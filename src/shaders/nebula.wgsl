@@ -0,0 +1,127 @@
+// Horneado de nebulosas y del brillo difuso de la Vía Láctea en un cubemap.
+// Se dibuja un triángulo a pantalla completa por cada cara.
+
+struct NebulaUniforms {
+    colors: array<vec4<f32>, 3>,
+    face: u32,
+    seed: u32,
+    density: f32,
+    scale: f32,
+    brightness: f32,
+    band_width: f32,     // Radianes
+    galactic_tilt: f32,  // Radianes
+    core_longitude: f32, // Radianes
+};
+
+@group(0) @binding(0) var<uniform> nebula: NebulaUniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) clip: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    let clip = uv * 2.0 - 1.0;
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(clip, 0.0, 1.0);
+    out.clip = clip;
+    return out;
+}
+
+// Igual que `skybox::cube_face_direction` (v crece hacia abajo en la textura)
+fn cube_face_direction(face: u32, u: f32, v: f32) -> vec3<f32> {
+    switch face {
+        case 0u: { return vec3<f32>(1.0, -v, -u); }
+        case 1u: { return vec3<f32>(-1.0, -v, u); }
+        case 2u: { return vec3<f32>(u, 1.0, v); }
+        case 3u: { return vec3<f32>(u, -1.0, -v); }
+        case 4u: { return vec3<f32>(u, -v, 1.0); }
+        default: { return vec3<f32>(-u, -v, -1.0); }
+    }
+}
+
+fn hash3(p: vec3<f32>) -> f32 {
+    let q = fract(p * 0.3183099 + vec3<f32>(0.1, 0.2, 0.3));
+    let r = q * 17.0;
+    return fract(r.x * r.y * r.z * (r.x + r.y + r.z));
+}
+
+fn value_noise(p: vec3<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let w = f * f * (3.0 - 2.0 * f);
+
+    let n000 = hash3(i);
+    let n100 = hash3(i + vec3<f32>(1.0, 0.0, 0.0));
+    let n010 = hash3(i + vec3<f32>(0.0, 1.0, 0.0));
+    let n110 = hash3(i + vec3<f32>(1.0, 1.0, 0.0));
+    let n001 = hash3(i + vec3<f32>(0.0, 0.0, 1.0));
+    let n101 = hash3(i + vec3<f32>(1.0, 0.0, 1.0));
+    let n011 = hash3(i + vec3<f32>(0.0, 1.0, 1.0));
+    let n111 = hash3(i + vec3<f32>(1.0, 1.0, 1.0));
+
+    let x00 = mix(n000, n100, w.x);
+    let x10 = mix(n010, n110, w.x);
+    let x01 = mix(n001, n101, w.x);
+    let x11 = mix(n011, n111, w.x);
+    return mix(mix(x00, x10, w.y), mix(x01, x11, w.y), w.z);
+}
+
+fn fbm(p: vec3<f32>) -> f32 {
+    var sum = 0.0;
+    var amplitude = 0.5;
+    var position = p;
+    for (var octave = 0; octave < 6; octave = octave + 1) {
+        sum = sum + amplitude * value_noise(position);
+        position = position * 2.03;
+        amplitude = amplitude * 0.5;
+    }
+    return sum;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let direction = normalize(cube_face_direction(nebula.face, in.clip.x, -in.clip.y));
+
+    // Coordenadas galácticas: se deshace la inclinación usada para las estrellas
+    let c = cos(nebula.galactic_tilt);
+    let s = sin(nebula.galactic_tilt);
+    let galactic = vec3<f32>(
+        direction.x,
+        direction.y * c + direction.z * s,
+        -direction.y * s + direction.z * c,
+    );
+    let latitude = asin(clamp(galactic.y, -1.0, 1.0));
+    var longitude = atan2(galactic.z, galactic.x) - nebula.core_longitude;
+    longitude = longitude - 6.2831853 * round(longitude / 6.2831853);
+
+    let band = exp(-0.5 * pow(latitude / nebula.band_width, 2.0));
+    let wide_band = exp(-0.5 * pow(latitude / (nebula.band_width * 3.0), 2.0));
+    let core = exp(-longitude * longitude / 0.8);
+
+    let offset = vec3<f32>(f32(nebula.seed % 97u), f32(nebula.seed % 89u), f32(nebula.seed % 83u)) * 3.7;
+    let p = direction * nebula.scale + offset;
+
+    // Brillo difuso de estrellas no resueltas con franjas de polvo oscuro
+    let dust = smoothstep(0.45, 0.7, fbm(p * 2.0 + vec3<f32>(5.2, 1.3, 7.7)));
+    let glow = band * (0.25 + 0.75 * core) * (1.0 - 0.8 * dust * band);
+    let glow_color = vec3<f32>(0.9, 0.85, 0.75) * glow * 0.35;
+
+    // Nubes de gas con deformación de dominio, más densas cerca de la banda
+    let warp = vec3<f32>(fbm(p + vec3<f32>(1.7, 9.2, 3.1)), fbm(p + vec3<f32>(8.3, 2.8, 4.6)), fbm(p)) * 1.5;
+    let clouds = fbm(p + warp);
+    let coverage = smoothstep(1.0 - nebula.density, 1.0, clouds) * mix(0.3, 1.0, wide_band);
+
+    let tint = fbm(p * 0.7 + vec3<f32>(3.0, 3.0, 3.0));
+    let cloud_color = mix(
+        mix(nebula.colors[0].rgb, nebula.colors[1].rgb, smoothstep(0.3, 0.6, tint)),
+        nebula.colors[2].rgb,
+        smoothstep(0.55, 0.75, tint),
+    );
+
+    let color = (glow_color + cloud_color * coverage) * nebula.brightness;
+    return vec4<f32>(color, 1.0);
+}
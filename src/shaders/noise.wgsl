@@ -0,0 +1,188 @@
+// Biblioteca de ruido compartida por los shaders procedurales.
+// Todas las funciones de ruido devuelven valores aproximadamente en [-1, 1].
+
+// Hash sin senos (Dave Hoskins): tres valores pseudoaleatorios en [0, 1)
+fn hash33(p: vec3<f32>) -> vec3<f32> {
+    var q = fract(p * vec3<f32>(0.1031, 0.1030, 0.0973));
+    q = q + dot(q, q.yxz + 33.33);
+    return fract((q.xxy + q.yxx) * q.zyx);
+}
+
+// Desplazamiento del dominio propio de cada semilla, para que dos cuerpos no se repitan
+fn seed_offset(seed: u32) -> vec3<f32> {
+    let s = f32(seed % 4096u);
+    return hash33(vec3<f32>(s + 0.5, s * 1.37 + 11.0, s * 2.11 + 47.0)) * 512.0;
+}
+
+fn mod289_3(x: vec3<f32>) -> vec3<f32> {
+    return x - floor(x * (1.0 / 289.0)) * 289.0;
+}
+
+fn mod289_4(x: vec4<f32>) -> vec4<f32> {
+    return x - floor(x * (1.0 / 289.0)) * 289.0;
+}
+
+fn permute4(x: vec4<f32>) -> vec4<f32> {
+    return mod289_4(((x * 34.0) + 1.0) * x);
+}
+
+fn taylor_inv_sqrt4(r: vec4<f32>) -> vec4<f32> {
+    return 1.79284291400159 - 0.85373472095314 * r;
+}
+
+// Ruido simplex 3D (Ashima Arts / Stefan Gustavson, licencia MIT)
+fn simplex3(v: vec3<f32>) -> f32 {
+    let C = vec2<f32>(1.0 / 6.0, 1.0 / 3.0);
+    let D = vec4<f32>(0.0, 0.5, 1.0, 2.0);
+
+    // Primera esquina
+    var i = floor(v + dot(v, C.yyy));
+    let x0 = v - i + dot(i, C.xxx);
+
+    // Otras esquinas
+    let g = step(x0.yzx, x0.xyz);
+    let l = 1.0 - g;
+    let i1 = min(g.xyz, l.zxy);
+    let i2 = max(g.xyz, l.zxy);
+
+    let x1 = x0 - i1 + C.xxx;
+    let x2 = x0 - i2 + C.yyy;
+    let x3 = x0 - D.yyy;
+
+    // Permutaciones
+    i = mod289_3(i);
+    let p = permute4(permute4(permute4(
+                i.z + vec4<f32>(0.0, i1.z, i2.z, 1.0))
+              + i.y + vec4<f32>(0.0, i1.y, i2.y, 1.0))
+              + i.x + vec4<f32>(0.0, i1.x, i2.x, 1.0));
+
+    // Gradientes: 7x7 puntos sobre un cuadrado, proyectados en un octaedro
+    let n_ = 0.142857142857;
+    let ns = n_ * D.wyz - D.xzx;
+
+    let j = p - 49.0 * floor(p * ns.z * ns.z);
+
+    let x_ = floor(j * ns.z);
+    let y_ = floor(j - 7.0 * x_);
+
+    let x = x_ * ns.x + ns.yyyy;
+    let y = y_ * ns.x + ns.yyyy;
+    let h = 1.0 - abs(x) - abs(y);
+
+    let b0 = vec4<f32>(x.xy, y.xy);
+    let b1 = vec4<f32>(x.zw, y.zw);
+
+    let s0 = floor(b0) * 2.0 + 1.0;
+    let s1 = floor(b1) * 2.0 + 1.0;
+    let sh = -step(h, vec4<f32>(0.0));
+
+    let a0 = b0.xzyw + s0.xzyw * sh.xxyy;
+    let a1 = b1.xzyw + s1.xzyw * sh.zzww;
+
+    // Normalización de los gradientes
+    let norm = taylor_inv_sqrt4(vec4<f32>(
+        dot(vec3<f32>(a0.xy, h.x), vec3<f32>(a0.xy, h.x)),
+        dot(vec3<f32>(a0.zw, h.y), vec3<f32>(a0.zw, h.y)),
+        dot(vec3<f32>(a1.xy, h.z), vec3<f32>(a1.xy, h.z)),
+        dot(vec3<f32>(a1.zw, h.w), vec3<f32>(a1.zw, h.w)),
+    ));
+    let p0 = vec3<f32>(a0.xy, h.x) * norm.x;
+    let p1 = vec3<f32>(a0.zw, h.y) * norm.y;
+    let p2 = vec3<f32>(a1.xy, h.z) * norm.z;
+    let p3 = vec3<f32>(a1.zw, h.w) * norm.w;
+
+    // Mezcla de las contribuciones de cada esquina
+    var m = max(0.6 - vec4<f32>(dot(x0, x0), dot(x1, x1), dot(x2, x2), dot(x3, x3)), vec4<f32>(0.0));
+    m = m * m;
+    return 42.0 * dot(m * m, vec4<f32>(dot(p0, x0), dot(p1, x1), dot(p2, x2), dot(p3, x3)));
+}
+
+// Ruido de gradiente (Perlin) con interpolación quíntica
+fn perlin3(p: vec3<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+
+    let g000 = dot(hash33(i) * 2.0 - 1.0, f);
+    let g100 = dot(hash33(i + vec3<f32>(1.0, 0.0, 0.0)) * 2.0 - 1.0, f - vec3<f32>(1.0, 0.0, 0.0));
+    let g010 = dot(hash33(i + vec3<f32>(0.0, 1.0, 0.0)) * 2.0 - 1.0, f - vec3<f32>(0.0, 1.0, 0.0));
+    let g110 = dot(hash33(i + vec3<f32>(1.0, 1.0, 0.0)) * 2.0 - 1.0, f - vec3<f32>(1.0, 1.0, 0.0));
+    let g001 = dot(hash33(i + vec3<f32>(0.0, 0.0, 1.0)) * 2.0 - 1.0, f - vec3<f32>(0.0, 0.0, 1.0));
+    let g101 = dot(hash33(i + vec3<f32>(1.0, 0.0, 1.0)) * 2.0 - 1.0, f - vec3<f32>(1.0, 0.0, 1.0));
+    let g011 = dot(hash33(i + vec3<f32>(0.0, 1.0, 1.0)) * 2.0 - 1.0, f - vec3<f32>(0.0, 1.0, 1.0));
+    let g111 = dot(hash33(i + vec3<f32>(1.0, 1.0, 1.0)) * 2.0 - 1.0, f - vec3<f32>(1.0, 1.0, 1.0));
+
+    let x00 = mix(g000, g100, u.x);
+    let x10 = mix(g010, g110, u.x);
+    let x01 = mix(g001, g101, u.x);
+    let x11 = mix(g011, g111, u.x);
+    return mix(mix(x00, x10, u.y), mix(x01, x11, u.y), u.z) * 1.7;
+}
+
+// Movimiento browniano fraccional sobre ruido simplex
+fn fbm3(p: vec3<f32>, octaves: i32) -> f32 {
+    var sum = 0.0;
+    var amplitude = 0.5;
+    var normalization = 0.0;
+    var position = p;
+    for (var octave = 0; octave < octaves; octave = octave + 1) {
+        sum = sum + amplitude * simplex3(position);
+        normalization = normalization + amplitude;
+        position = position * 2.02 + vec3<f32>(17.3, 5.1, 9.7);
+        amplitude = amplitude * 0.5;
+    }
+    return sum / normalization;
+}
+
+// fBm con crestas: picos afilados para cordilleras y tormentas; devuelve [0, 1]
+fn ridged3(p: vec3<f32>, octaves: i32) -> f32 {
+    var sum = 0.0;
+    var amplitude = 0.5;
+    var normalization = 0.0;
+    var weight = 1.0;
+    var position = p;
+    for (var octave = 0; octave < octaves; octave = octave + 1) {
+        var ridge = 1.0 - abs(simplex3(position));
+        ridge = ridge * ridge * weight;
+        weight = clamp(ridge * 2.0, 0.0, 1.0);
+        sum = sum + amplitude * ridge;
+        normalization = normalization + amplitude;
+        position = position * 2.03 + vec3<f32>(3.7, 11.9, 1.3);
+        amplitude = amplitude * 0.5;
+    }
+    return sum / normalization;
+}
+
+// Deformación de dominio: desplaza el punto con otro fBm para formas más orgánicas
+fn domain_warp3(p: vec3<f32>, strength: f32) -> vec3<f32> {
+    let offset = vec3<f32>(
+        fbm3(p + vec3<f32>(0.0, 0.0, 0.0), 3),
+        fbm3(p + vec3<f32>(5.2, 1.3, 2.8), 3),
+        fbm3(p + vec3<f32>(1.7, 9.2, 4.1), 3),
+    );
+    return p + offset * strength;
+}
+
+// Ruido celular (Worley): distancia al punto más cercano (x) y al segundo (y)
+fn worley3(p: vec3<f32>) -> vec2<f32> {
+    let cell = floor(p);
+    let local = fract(p);
+    var f1 = 8.0;
+    var f2 = 8.0;
+    for (var z = -1; z <= 1; z = z + 1) {
+        for (var y = -1; y <= 1; y = y + 1) {
+            for (var x = -1; x <= 1; x = x + 1) {
+                let neighbor = vec3<f32>(f32(x), f32(y), f32(z));
+                let feature = neighbor + hash33(cell + neighbor) - local;
+                let distance = dot(feature, feature);
+                if distance < f1 {
+                    f2 = f1;
+                    f1 = distance;
+                } else if distance < f2 {
+                    f2 = distance;
+                }
+            }
+        }
+    }
+    return sqrt(vec2<f32>(f1, f2));
+}
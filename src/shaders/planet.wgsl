@@ -1,18 +1,261 @@
+// Shader único de los planetas; el aspecto de cada cuerpo sale de su bloque `Material`.
+
+#include "common.wgsl"
+#include "noise.wgsl"
+#include "lighting.wgsl"
+#include "cloud_density.wgsl"
+#include "shadows.wgsl"
+
+// Debe coincidir con `material::MaterialUniforms`
+struct Material {
+    colors: array<vec4<f32>, 3>,
+    city_color: vec4<f32>, // Color e intensidad de las luces nocturnas
+    kind: u32,
+    band_count: f32,
+    noise_frequency: f32,
+    detail_frequency: f32,
+    ambient: f32,
+    emission: f32,
+    seed: u32,
+    cloud_coverage: f32, // Capa de nubes que da sombra; 0 = sin nubes
+    cloud_height: f32,
+    cloud_drift: f32,
+    cloud_shadow: f32,
+    cloud_seed: u32,
+    city_density: f32,
+    specular: f32, // Reflejo del Sol sobre el agua
+    shininess: f32,
+};
+
+@group(0) @binding(1) var<uniform> material: Material;
+
+const KIND_ROCKY: u32 = 0u;
+const KIND_GAS_GIANT: u32 = 1u;
+const KIND_OCEAN: u32 = 2u;
+const KIND_ICE_GIANT: u32 = 3u;
+const KIND_STAR: u32 = 4u;
+
+const SEA_LEVEL: f32 = 0.52;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) local_position: vec3<f32>, // Gira junto con el planeta
+    @location(1) world_normal: vec3<f32>,
+    @location(2) world_position: vec3<f32>,
+};
+
+#ifdef TERRAIN
+// Parches del terreno: la posición ya trae el relieve y la normal sale de sus pendientes
 @vertex
-fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
-    // Define positions for the triangle vertices
-    var pos: vec2<f32>;
-    switch (vertex_index) {
-        case 0u: { pos = vec2<f32>(0.0, 0.5); }
-        case 1u: { pos = vec2<f32>(-0.5, -0.5); }
-        case 2u: { pos = vec2<f32>(0.5, -0.5); }
-        default: { pos = vec2<f32>(0.0, 0.0); } // Fallback (not used but required)
+fn vs_main(@location(0) position: vec3<f32>, @location(1) normal: vec3<f32>) -> VertexOutput {
+    let world = uniforms.model * vec4<f32>(position, 1.0);
+
+    var out: VertexOutput;
+    out.clip_position = uniforms.view_proj * world;
+    out.local_position = position;
+    out.world_normal = normalize((uniforms.model * vec4<f32>(normal, 0.0)).xyz);
+    out.world_position = world.xyz;
+    return out;
+}
+#else
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> VertexOutput {
+    let world = uniforms.model * vec4<f32>(position, 1.0);
+
+    var out: VertexOutput;
+    out.clip_position = uniforms.view_proj * world;
+    out.local_position = position;
+    out.world_normal = normalize((uniforms.model * vec4<f32>(position, 0.0)).xyz);
+    out.world_position = world.xyz;
+    return out;
+}
+#endif
+
+// Continentes con fBm deformado y cráteres a partir de celdas de Worley
+fn rocky_surface(p: vec3<f32>, seed: vec3<f32>) -> vec3<f32> {
+    let q = p * material.noise_frequency + seed;
+    let continents = fbm3(domain_warp3(q, 0.7), 5) * 0.5 + 0.5;
+    var color = mix(material.colors[0].rgb, material.colors[1].rgb, smoothstep(0.35, 0.65, continents));
+
+    let cells = worley3(p * material.detail_frequency + seed);
+    let crater_floor = 1.0 - smoothstep(0.15, 0.3, cells.x);
+    let crater_rim = smoothstep(0.25, 0.32, cells.x) * (1.0 - smoothstep(0.32, 0.42, cells.x));
+    color = mix(color, material.colors[2].rgb, crater_floor * 0.7);
+    color = color * (1.0 + crater_rim * 0.3);
+
+    // Grano fino de la superficie
+    return color * (0.9 + 0.1 * simplex3(p * material.detail_frequency * 4.0 + seed));
+}
+
+// Bandas de latitud deformadas por turbulencia y tormentas ovaladas
+fn gas_giant_surface(p: vec3<f32>, seed: vec3<f32>) -> vec3<f32> {
+    let flow = vec3<f32>(uniforms.time * 0.01, 0.0, 0.0);
+    let turbulence = fbm3(domain_warp3(p * material.detail_frequency + seed + flow, 0.5), 4);
+    let latitude = p.y + turbulence * 0.06;
+
+    let bands = sin(latitude * material.band_count * 3.1415927) * 0.5 + 0.5;
+    let streaks = fbm3(vec3<f32>(p.x * 2.0, latitude * material.band_count * 4.0, p.z * 2.0) + seed, 3);
+    let band_color = mix(material.colors[0].rgb, material.colors[1].rgb, smoothstep(0.25, 0.75, bands + streaks * 0.2));
+
+    let stretched = vec3<f32>(p.x, p.y * 2.5, p.z) * material.noise_frequency;
+    let storm = 1.0 - smoothstep(0.08, 0.22, worley3(stretched + seed).x);
+    return mix(band_color, material.colors[2].rgb, storm * 0.8);
+}
+
+// Elevación del terreno en [0, 1] para los mundos oceánicos
+fn ocean_elevation(p: vec3<f32>, seed: vec3<f32>) -> f32 {
+    return fbm3(domain_warp3(p * material.noise_frequency + seed, 0.8), 6) * 0.5 + 0.5;
+}
+
+// Fracción de tierra firme según la elevación: 0 en el mar, 1 sobre la costa
+fn ocean_land(elevation: f32) -> f32 {
+    return smoothstep(SEA_LEVEL, SEA_LEVEL + 0.015, elevation);
+}
+
+fn ocean_surface(p: vec3<f32>, seed: vec3<f32>, elevation: f32) -> vec3<f32> {
+    let sea_level = SEA_LEVEL;
+    let land = ocean_land(elevation);
+    let shallow = smoothstep(sea_level - 0.15, sea_level, elevation);
+    let water = mix(material.colors[0].rgb * 0.6, material.colors[0].rgb, shallow);
+
+    let ridges = ridged3(p * material.detail_frequency + seed, 5);
+    let highlands = smoothstep(sea_level + 0.02, sea_level + 0.2, elevation);
+    let land_color = mix(material.colors[1].rgb, material.colors[2].rgb, smoothstep(0.45, 0.8, ridges) * highlands);
+
+    // Casquetes polares
+    let ice = smoothstep(0.82, 0.9, abs(p.y) + 0.05 * simplex3(p * 8.0 + seed));
+    return mix(mix(water, land_color, land), vec3<f32>(0.95, 0.97, 1.0), ice);
+}
+
+// Bandas suaves y neblina uniforme
+fn ice_giant_surface(p: vec3<f32>, seed: vec3<f32>) -> vec3<f32> {
+    let haze = fbm3(p * material.noise_frequency + seed, 4) * 0.5 + 0.5;
+    let latitude = p.y + fbm3(p * material.detail_frequency + seed, 3) * 0.03;
+    let bands = smoothstep(-0.3, 0.3, sin(latitude * material.band_count * 3.1415927));
+    let base = mix(material.colors[0].rgb, material.colors[1].rgb, bands * 0.5);
+    return mix(base, material.colors[2].rgb, smoothstep(0.55, 0.8, haze) * 0.5);
+}
+
+// Fotosfera animada: granulación que hierve, manchas que derivan con la rotación diferencial
+// y oscurecimiento hacia el limbo. `view_cos` es el coseno entre la normal y la dirección a la cámara.
+fn star_surface(p: vec3<f32>, seed: vec3<f32>, view_cos: f32) -> vec3<f32> {
+    let time = uniforms.time;
+
+    // Rotación diferencial: el ecuador adelanta a los polos además del giro del cuerpo
+    let angle = time * 0.015 * (1.0 - 0.4 * p.y * p.y);
+    let q = vec3<f32>(p.x * cos(angle) - p.z * sin(angle), p.y, p.x * sin(angle) + p.z * cos(angle));
+
+    // Granulación: celdas de convección deformadas por un ruido que cambia con el tiempo
+    let boil = vec3<f32>(time * 0.05, time * 0.03, -time * 0.04);
+    let warp = vec3<f32>(
+        simplex3(q * 8.0 + boil),
+        simplex3(q * 8.0 + boil + vec3<f32>(17.0)),
+        simplex3(q * 8.0 + boil + vec3<f32>(31.0)),
+    );
+    let cells = worley3(q * material.noise_frequency + seed + warp * 0.35);
+    let granule = smoothstep(0.0, 0.35, cells.y - cells.x);
+    let fine = fbm3(q * material.detail_frequency + seed + boil, 3) * 0.5 + 0.5;
+    var color = mix(material.colors[2].rgb, material.colors[1].rgb, granule) * (0.85 + 0.3 * fine);
+
+    // Manchas solares en latitudes medias; el ruido lento las hace aparecer y disolverse
+    let activity = smoothstep(0.05, 0.2, abs(p.y)) * (1.0 - smoothstep(0.45, 0.6, abs(p.y)));
+    let spots = (fbm3(q * 3.0 + seed.zxy + vec3<f32>(0.0, time * 0.01, 0.0), 4) * 0.5 + 0.5) * activity;
+    color = mix(color, material.colors[0].rgb * 0.6, smoothstep(0.64, 0.68, spots) * 0.7); // Penumbra
+    color = mix(color, material.colors[0].rgb * 0.15, smoothstep(0.7, 0.73, spots));       // Umbra
+
+    // Oscurecimiento del limbo (ley cuadrática) con el borde más rojizo
+    let mu = clamp(view_cos, 0.0, 1.0);
+    let limb = 1.0 - 0.5 * (1.0 - mu) - 0.25 * (1.0 - mu) * (1.0 - mu);
+    return color * limb * mix(vec3<f32>(1.0, 0.6, 0.35), vec3<f32>(1.0), sqrt(mu));
+}
+
+// Densidad de la capa de nubes sobre el punto `p` de la superficie, en la dirección local
+// `direction`. El punto de corte con la capa se lleva al marco de las nubes, que giran
+// `cloud_drift` radianes por segundo respecto al planeta.
+fn cloud_cover(p: vec3<f32>, direction: vec3<f32>) -> f32 {
+    if material.cloud_coverage <= 0.0 {
+        return 0.0;
+    }
+    let b = dot(p, direction);
+    let t = -b + sqrt(b * b - 1.0 + material.cloud_height * material.cloud_height);
+    let hit = normalize(p + direction * t);
+
+    let angle = -material.cloud_drift * uniforms.time;
+    let q = vec3<f32>(hit.x * cos(angle) + hit.z * sin(angle), hit.y, -hit.x * sin(angle) + hit.z * cos(angle));
+    return cloud_density(q, seed_offset(material.cloud_seed), material.cloud_coverage, uniforms.time);
+}
+
+// Luz que deja pasar la capa de nubes siguiendo el rayo hacia el Sol
+fn cloud_shadow(p: vec3<f32>, light_dir: vec3<f32>) -> f32 {
+    if material.cloud_coverage <= 0.0 {
+        return 1.0;
     }
+    // La rotación del modelo es ortonormal salvo la escala uniforme: su traspuesta la invierte
+    let rotation = mat3x3<f32>(
+        normalize(uniforms.model[0].xyz),
+        normalize(uniforms.model[1].xyz),
+        normalize(uniforms.model[2].xyz),
+    );
+    return 1.0 - cloud_cover(p, transpose(rotation) * light_dir) * material.cloud_shadow;
+}
 
-    return vec4<f32>(pos, 0.0, 1.0);
+// Luces de ciudades: regiones pobladas a gran escala y núcleos urbanos dentro de ellas
+fn city_lights(p: vec3<f32>, seed: vec3<f32>) -> f32 {
+    let regions = fbm3(p * 4.0 + seed.yzx, 4) * 0.5 + 0.5;
+    let populated = smoothstep(1.0 - material.city_density, 1.0 - material.city_density * 0.4, regions);
+    let cells = worley3(p * 60.0 + seed);
+    let towns = 1.0 - smoothstep(0.0, 0.3, cells.x);
+    let sprawl = simplex3(p * 240.0 + seed) * 0.5 + 0.5;
+    return populated * towns * (0.4 + 0.6 * sprawl);
 }
 
 @fragment
-fn fs_main() -> @location(0) vec4<f32> {
-    return vec4<f32>(1.0, 0.0, 0.0, 1.0); // Output color (red)
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let p = normalize(in.local_position);
+    let seed = seed_offset(material.seed);
+
+    if material.kind == KIND_STAR {
+        let view_cos = dot(normalize(in.world_normal), normalize(uniforms.camera_position.xyz - in.world_position));
+        return vec4<f32>(star_surface(p, seed, view_cos) * material.emission, 1.0);
+    }
+
+    // Agua y tierra habitable: los mundos oceánicos las separan por la elevación,
+    // en los demás no hay agua y las ciudades (si las hay) ocupan toda la superficie
+    var water = 0.0;
+    var habitable = 1.0;
+    var albedo: vec3<f32>;
+    if material.kind == KIND_GAS_GIANT {
+        albedo = gas_giant_surface(p, seed);
+    } else if material.kind == KIND_OCEAN {
+        let elevation = ocean_elevation(p, seed);
+        albedo = ocean_surface(p, seed, elevation);
+        water = 1.0 - ocean_land(elevation);
+        habitable = (1.0 - water) * (1.0 - smoothstep(0.7, 0.8, abs(p.y)));
+    } else if material.kind == KIND_ICE_GIANT {
+        albedo = ice_giant_surface(p, seed);
+    } else {
+        albedo = rocky_surface(p, seed);
+    }
+
+    let normal = normalize(in.world_normal);
+    let light_dir = sun_direction(in.world_position);
+    let shadow = cloud_shadow(p, light_dir) * sun_visibility(in.world_position);
+    let diffuse = lambert(normal, light_dir) * shadow;
+    var color = (material.ambient + diffuse) * albedo;
+
+    // Reflejo del Sol sobre el agua, por encima de 1 en HDR para que el destello genere bloom
+    if material.specular > 0.0 && water > 0.0 {
+        let view_dir = normalize(uniforms.camera_position.xyz - in.world_position);
+        color += vec3<f32>(blinn_phong(normal, light_dir, view_dir, material.shininess) * material.specular * water * shadow);
+    }
+
+    // Luces de ciudades solo en el lado nocturno, con una transición suave en el terminador;
+    // las nubes que pasan por encima las tapan
+    if material.city_color.a > 0.0 && habitable > 0.0 {
+        let night = 1.0 - smoothstep(-0.1, 0.1, dot(normal, light_dir));
+        let lights = city_lights(p, seed) * habitable * night * (1.0 - cloud_cover(p, p) * 0.8);
+        color += material.city_color.rgb * material.city_color.a * lights;
+    }
+
+    return vec4<f32>(color, uniforms.color.a);
 }
@@ -0,0 +1,231 @@
+// Posprocesado a pantalla completa: extracción de brillos, cadena de bloom
+// (reducción con filtro de 13 muestras y ampliación con filtro tienda) y tonemapping.
+
+// Debe coincidir con `postprocess::PostUniforms`
+struct PostUniforms {
+    threshold: f32,
+    knee: f32,
+    intensity: f32,
+    exposure: f32,
+    filter_radius: f32,
+};
+
+@group(0) @binding(0) var<uniform> post: PostUniforms;
+@group(0) @binding(1) var linear_sampler: sampler;
+@group(1) @binding(0) var source: texture_2d<f32>;
+@group(2) @binding(0) var bloom: texture_2d<f32>; // Solo en el tonemapping
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Un triángulo que cubre toda la pantalla
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let position = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u)) * 2.0 - 1.0;
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = vec2<f32>(position.x * 0.5 + 0.5, 0.5 - position.y * 0.5);
+    return out;
+}
+
+fn sample_source(uv: vec2<f32>) -> vec3<f32> {
+    return textureSample(source, linear_sampler, uv).rgb;
+}
+
+// Reducción a la mitad con 13 muestras: evita el parpadeo de los brillos pequeños
+fn downsample13(uv: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source));
+    let a = sample_source(uv + texel * vec2<f32>(-2.0, -2.0));
+    let b = sample_source(uv + texel * vec2<f32>(0.0, -2.0));
+    let c = sample_source(uv + texel * vec2<f32>(2.0, -2.0));
+    let d = sample_source(uv + texel * vec2<f32>(-2.0, 0.0));
+    let e = sample_source(uv);
+    let f = sample_source(uv + texel * vec2<f32>(2.0, 0.0));
+    let g = sample_source(uv + texel * vec2<f32>(-2.0, 2.0));
+    let h = sample_source(uv + texel * vec2<f32>(0.0, 2.0));
+    let i = sample_source(uv + texel * vec2<f32>(2.0, 2.0));
+    let j = sample_source(uv + texel * vec2<f32>(-1.0, -1.0));
+    let k = sample_source(uv + texel * vec2<f32>(1.0, -1.0));
+    let l = sample_source(uv + texel * vec2<f32>(-1.0, 1.0));
+    let m = sample_source(uv + texel * vec2<f32>(1.0, 1.0));
+
+    var color = e * 0.125;
+    color += (a + c + g + i) * 0.03125;
+    color += (b + d + f + h) * 0.0625;
+    color += (j + k + l + m) * 0.125;
+    return color;
+}
+
+@fragment
+fn fs_prefilter(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Se limita el brillo para que un píxel aislado muy intenso no domine el bloom
+    let color = min(downsample13(in.uv), vec3<f32>(64.0));
+
+    // Umbral con transición cuadrática suave
+    let brightness = max(color.r, max(color.g, color.b));
+    var soft = clamp(brightness - post.threshold + post.knee, 0.0, 2.0 * post.knee);
+    soft = soft * soft / (4.0 * post.knee + 0.0001);
+    let contribution = max(soft, brightness - post.threshold) / max(brightness, 0.0001);
+    return vec4<f32>(color * contribution, 1.0);
+}
+
+@fragment
+fn fs_downsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(downsample13(in.uv), 1.0);
+}
+
+// Filtro tienda de 3×3 muestras; se suma sobre el nivel de mayor resolución
+@fragment
+fn fs_upsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    let offset = post.filter_radius / vec2<f32>(textureDimensions(source));
+    var color = sample_source(in.uv) * 4.0;
+    color += (sample_source(in.uv + vec2<f32>(-offset.x, 0.0)) + sample_source(in.uv + vec2<f32>(offset.x, 0.0))) * 2.0;
+    color += (sample_source(in.uv + vec2<f32>(0.0, -offset.y)) + sample_source(in.uv + vec2<f32>(0.0, offset.y))) * 2.0;
+    color += sample_source(in.uv - offset) + sample_source(in.uv + offset);
+    color += sample_source(in.uv + vec2<f32>(-offset.x, offset.y)) + sample_source(in.uv + vec2<f32>(offset.x, -offset.y));
+    return vec4<f32>(color / 16.0, 1.0);
+}
+
+// Aproximación de la curva ACES (Narkowicz)
+fn aces(color: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+@fragment
+fn fs_tonemap(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = sample_source(in.uv);
+    let glow = textureSample(bloom, linear_sampler, in.uv).rgb;
+    let color = (scene + glow * post.intensity) * post.exposure;
+    // La swapchain sRGB aplica la corrección gamma al escribir
+    return vec4<f32>(aces(color), 1.0);
+}
+
+// FXAA: busca los bordes por contraste de luminancia, recorre cada borde hasta sus extremos
+// y vuelve a muestrear desplazado hacia el otro lado según la posición del píxel en él
+const FXAA_EDGE_THRESHOLD: f32 = 0.125;     // Contraste mínimo relativo al brillo local
+const FXAA_EDGE_THRESHOLD_MIN: f32 = 0.0312; // Contraste mínimo en zonas oscuras
+const FXAA_SEARCH_STEPS: i32 = 10;
+const FXAA_SUBPIXEL: f32 = 0.75; // Suavizado de detalles menores que un píxel
+
+fn sample_level(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(source, linear_sampler, uv, 0.0).rgb;
+}
+
+fn luma(color: vec3<f32>) -> f32 {
+    let value = dot(color, vec3<f32>(0.299, 0.587, 0.114));
+#ifdef SRGB_SURFACE
+    return sqrt(value); // Aproximación de la corrección gamma
+#else
+    return value;
+#endif
+}
+
+// Los primeros pasos de la búsqueda avanzan de a un píxel y los últimos más rápido
+fn fxaa_stride(step: i32) -> f32 {
+    return select(select(4.0, 2.0, step < 8), 1.0, step < 4);
+}
+
+@fragment
+fn fs_fxaa(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source));
+    let center = sample_level(in.uv);
+    let luma_center = luma(center);
+    let luma_north = luma(sample_level(in.uv + vec2<f32>(0.0, -texel.y)));
+    let luma_south = luma(sample_level(in.uv + vec2<f32>(0.0, texel.y)));
+    let luma_east = luma(sample_level(in.uv + vec2<f32>(texel.x, 0.0)));
+    let luma_west = luma(sample_level(in.uv + vec2<f32>(-texel.x, 0.0)));
+
+    let luma_min = min(luma_center, min(min(luma_north, luma_south), min(luma_east, luma_west)));
+    let luma_max = max(luma_center, max(max(luma_north, luma_south), max(luma_east, luma_west)));
+    let range = luma_max - luma_min;
+    if range < max(FXAA_EDGE_THRESHOLD_MIN, luma_max * FXAA_EDGE_THRESHOLD) {
+        return vec4<f32>(center, 1.0);
+    }
+
+    let luma_north_west = luma(sample_level(in.uv - texel));
+    let luma_south_east = luma(sample_level(in.uv + texel));
+    let luma_north_east = luma(sample_level(in.uv + vec2<f32>(texel.x, -texel.y)));
+    let luma_south_west = luma(sample_level(in.uv + vec2<f32>(-texel.x, texel.y)));
+
+    // Orientación del borde
+    let horizontal = abs(luma_north + luma_south - 2.0 * luma_center) * 2.0
+        + abs(luma_north_east + luma_south_east - 2.0 * luma_east)
+        + abs(luma_north_west + luma_south_west - 2.0 * luma_west);
+    let vertical = abs(luma_east + luma_west - 2.0 * luma_center) * 2.0
+        + abs(luma_north_east + luma_north_west - 2.0 * luma_north)
+        + abs(luma_south_east + luma_south_west - 2.0 * luma_south);
+    let is_horizontal = horizontal >= vertical;
+
+    // Lado del borde con mayor contraste
+    let luma_negative = select(luma_west, luma_north, is_horizontal);
+    let luma_positive = select(luma_east, luma_south, is_horizontal);
+    let gradient_negative = luma_negative - luma_center;
+    let gradient_positive = luma_positive - luma_center;
+    let negative_steepest = abs(gradient_negative) >= abs(gradient_positive);
+    let gradient_scaled = 0.25 * max(abs(gradient_negative), abs(gradient_positive));
+
+    var step_length = select(texel.x, texel.y, is_horizontal);
+    var luma_edge = 0.5 * (luma_positive + luma_center);
+    if negative_steepest {
+        step_length = -step_length;
+        luma_edge = 0.5 * (luma_negative + luma_center);
+    }
+
+    // Recorre el borde en ambos sentidos, a medio píxel de distancia del centro
+    var edge_uv = in.uv;
+    if is_horizontal {
+        edge_uv.y += step_length * 0.5;
+    } else {
+        edge_uv.x += step_length * 0.5;
+    }
+    let offset = select(vec2<f32>(0.0, texel.y), vec2<f32>(texel.x, 0.0), is_horizontal);
+    var uv_negative = edge_uv - offset;
+    var uv_positive = edge_uv + offset;
+    var end_negative = luma(sample_level(uv_negative)) - luma_edge;
+    var end_positive = luma(sample_level(uv_positive)) - luma_edge;
+    var reached_negative = abs(end_negative) >= gradient_scaled;
+    var reached_positive = abs(end_positive) >= gradient_scaled;
+    for (var i = 0; i < FXAA_SEARCH_STEPS && !(reached_negative && reached_positive); i++) {
+        if !reached_negative {
+            uv_negative -= offset * fxaa_stride(i);
+            end_negative = luma(sample_level(uv_negative)) - luma_edge;
+            reached_negative = abs(end_negative) >= gradient_scaled;
+        }
+        if !reached_positive {
+            uv_positive += offset * fxaa_stride(i);
+            end_positive = luma(sample_level(uv_positive)) - luma_edge;
+            reached_positive = abs(end_positive) >= gradient_scaled;
+        }
+    }
+
+    // Desplazamiento según el extremo más cercano, solo si el borde va en la dirección correcta
+    let to_negative = select(in.uv.y - uv_negative.y, in.uv.x - uv_negative.x, is_horizontal);
+    let to_positive = select(uv_positive.y - in.uv.y, uv_positive.x - in.uv.x, is_horizontal);
+    let negative_closer = to_negative < to_positive;
+    let edge_offset = 0.5 - min(to_negative, to_positive) / (to_negative + to_positive);
+    let center_darker = luma_center < luma_edge;
+    let correct = (select(end_positive, end_negative, negative_closer) < 0.0) != center_darker;
+    var final_offset = select(0.0, edge_offset, correct);
+
+    // Suavizado de subpíxel a partir del promedio de los ocho vecinos
+    let luma_average = (2.0 * (luma_north + luma_south + luma_east + luma_west)
+        + luma_north_west + luma_north_east + luma_south_west + luma_south_east) / 12.0;
+    let subpixel = clamp(abs(luma_average - luma_center) / range, 0.0, 1.0);
+    let subpixel_smooth = (-2.0 * subpixel + 3.0) * subpixel * subpixel;
+    final_offset = max(final_offset, subpixel_smooth * subpixel_smooth * FXAA_SUBPIXEL);
+
+    var final_uv = in.uv;
+    if is_horizontal {
+        final_uv.y += final_offset * step_length;
+    } else {
+        final_uv.x += final_offset * step_length;
+    }
+    return vec4<f32>(sample_level(final_uv), 1.0);
+}
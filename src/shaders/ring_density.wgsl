@@ -0,0 +1,13 @@
+// Densidad de los anillos, compartida por el anillo y por la sombra que proyecta
+
+#include "noise.wgsl"
+
+// Densidad en función de `t`, la posición radial de 0 (borde interior) a 1 (exterior):
+// bandas anchas, surcos finos y divisiones vacías
+fn ring_density(t: f32, band_count: f32, seed: vec3<f32>) -> f32 {
+    let bands = fbm3(vec3<f32>(t * band_count, seed.y, seed.z), 4) * 0.5 + 0.5;
+    let grooves = simplex3(vec3<f32>(t * band_count * 9.0, seed.z, seed.x)) * 0.5 + 0.5;
+    let gaps = smoothstep(0.2, 0.35, fbm3(vec3<f32>(t * band_count * 0.5, seed.x, seed.y), 3) * 0.5 + 0.5);
+    let density = smoothstep(0.25, 0.75, bands) * (0.65 + 0.35 * grooves) * gaps;
+    return density * smoothstep(0.0, 0.04, t) * (1.0 - smoothstep(0.94, 1.0, t));
+}
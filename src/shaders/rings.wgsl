@@ -0,0 +1,54 @@
+// Anillos planetarios: un anillo plano translúcido con bandas radiales de densidad
+// y la sombra del planeta proyectada sobre él.
+
+#include "common.wgsl"
+#include "ring_density.wgsl"
+#include "shadows.wgsl"
+
+// Debe coincidir con `rings::RingUniforms`
+struct Ring {
+    inner_color: vec4<f32>,
+    outer_color: vec4<f32>,
+    inner_radius: f32, // En radios del planeta
+    outer_radius: f32,
+    opacity: f32,
+    band_count: f32,
+    seed: u32,
+};
+
+@group(0) @binding(1) var<uniform> ring: Ring;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) local_position: vec3<f32>,
+    @location(1) world_position: vec3<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> VertexOutput {
+    let world = uniforms.model * vec4<f32>(position, 1.0);
+
+    var out: VertexOutput;
+    out.clip_position = uniforms.view_proj * world;
+    out.local_position = position;
+    out.world_position = world.xyz;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Bordes exactamente circulares aunque la malla sea poligonal
+    let radius = length(in.local_position.xz);
+    if radius < ring.inner_radius || radius > ring.outer_radius {
+        discard;
+    }
+    let t = (radius - ring.inner_radius) / (ring.outer_radius - ring.inner_radius);
+
+    let density = ring_density(t, ring.band_count, seed_offset(ring.seed));
+
+    // Sombra del planeta, de sus lunas y de la nave; el anillo no se da sombra a sí mismo
+    let light = body_shadow(in.world_position) * caster_shadow(in.world_position);
+
+    let color = mix(ring.inner_color.rgb, ring.outer_color.rgb, t) * (0.12 + 0.88 * light);
+    return vec4<f32>(color, density * ring.opacity * uniforms.color.a);
+}
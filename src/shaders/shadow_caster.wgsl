@@ -0,0 +1,9 @@
+// Profundidad de las mallas vistas desde el Sol, para el shadow map
+
+#include "common.wgsl"
+#include "shadows.wgsl"
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return shadows.light_view_proj * uniforms.model * vec4<f32>(position, 1.0);
+}
@@ -0,0 +1,143 @@
+// Sombras del Sol. Los cuerpos son esferas y los anillos discos, así que su sombra se
+// calcula de forma analítica (eclipses, sombras sobre y de los anillos); las mallas como
+// la nave se dibujan en un shadow map visto desde el Sol.
+
+#include "ring_density.wgsl"
+
+const MAX_OCCLUDERS: u32 = 32u;
+const MAX_RING_OCCLUDERS: u32 = 4u;
+
+// Debe coincidir con `shadows::RingOccluder`
+struct RingOccluder {
+    center: vec4<f32>, // Centro (xyz) y opacidad (w)
+    axis: vec4<f32>,   // Normal del plano (xyz) y cantidad de bandas (w)
+    inner_radius: f32,
+    outer_radius: f32,
+    seed: u32,
+};
+
+// Debe coincidir con `shadows::ShadowUniforms`
+struct Shadows {
+    light_view_proj: mat4x4<f32>, // Proyección del shadow map
+    sun: vec4<f32>,               // Centro (xyz) y radio (w) del Sol
+    caster: vec4<f32>,            // Medio ancho de la caja, profundidad que cubre e intensidad
+    occluder_count: u32,
+    ring_count: u32,
+    occluders: array<vec4<f32>, MAX_OCCLUDERS>, // Centro (xyz) y radio (w) de cada cuerpo
+    rings: array<RingOccluder, MAX_RING_OCCLUDERS>,
+};
+
+@group(1) @binding(0) var<uniform> shadows: Shadows;
+@group(1) @binding(1) var shadow_map: texture_depth_2d;
+@group(1) @binding(2) var shadow_sampler: sampler_comparison;
+
+// Dirección hacia el centro del Sol, según la posición del nodo del Sol en el grafo de escena
+fn sun_direction(world_position: vec3<f32>) -> vec3<f32> {
+    return normalize(shadows.sun.xyz - world_position);
+}
+
+// Fracción del disco del Sol que una esfera deja ver desde `point`. Según los tamaños
+// aparentes de la esfera y del Sol la sombra tiene umbra, penumbra o un eclipse anular.
+fn sphere_occlusion(point: vec3<f32>, center: vec3<f32>, radius: f32) -> f32 {
+    let to_sun = shadows.sun.xyz - point;
+    let sun_distance = length(to_sun);
+    let to_occluder = center - point;
+    let occluder_distance = length(to_occluder);
+    // Un cuerpo no se da sombra a sí mismo (eso lo resuelve la iluminación difusa)
+    if occluder_distance <= radius * 1.001 || occluder_distance >= sun_distance || dot(to_occluder, to_sun) <= 0.0 {
+        return 1.0;
+    }
+
+    let sun_radius = asin(min(shadows.sun.w / sun_distance, 1.0));
+    let occluder_radius = asin(min(radius / occluder_distance, 1.0));
+    let separation = acos(clamp(dot(to_sun / sun_distance, to_occluder / occluder_distance), -1.0, 1.0));
+    let coverage = min(occluder_radius * occluder_radius / (sun_radius * sun_radius), 1.0);
+    let overlap = 1.0 - smoothstep(abs(sun_radius - occluder_radius), sun_radius + occluder_radius, separation);
+    return 1.0 - coverage * overlap;
+}
+
+// Luz que dejan pasar todos los cuerpos
+fn body_shadow(point: vec3<f32>) -> f32 {
+    var light = 1.0;
+    for (var i = 0u; i < min(shadows.occluder_count, MAX_OCCLUDERS); i++) {
+        let occluder = shadows.occluders[i];
+        light *= sphere_occlusion(point, occluder.xyz, occluder.w);
+    }
+    return light;
+}
+
+// Luz que dejan pasar los anillos: el rayo hacia el Sol atraviesa su plano una sola vez
+fn ring_shadow(point: vec3<f32>) -> f32 {
+    let to_sun = normalize(shadows.sun.xyz - point);
+    var light = 1.0;
+    for (var i = 0u; i < min(shadows.ring_count, MAX_RING_OCCLUDERS); i++) {
+        let ring = shadows.rings[i];
+        let facing = dot(to_sun, ring.axis.xyz);
+        if abs(facing) < 1e-4 {
+            continue; // Sol en el plano del anillo
+        }
+        let crossing = dot(ring.center.xyz - point, ring.axis.xyz) / facing;
+        if crossing <= ring.outer_radius * 0.001 {
+            continue; // El anillo queda detrás o el punto está sobre él
+        }
+        let radius = length(point + to_sun * crossing - ring.center.xyz);
+        if radius < ring.inner_radius || radius > ring.outer_radius {
+            continue;
+        }
+        let t = (radius - ring.inner_radius) / (ring.outer_radius - ring.inner_radius);
+        light *= 1.0 - ring_density(t, ring.axis.w, seed_offset(ring.seed)) * ring.center.w;
+    }
+    return light;
+}
+
+// Sombra de las mallas a partir del shadow map. La penumbra se ensancha con la distancia a
+// la malla según el tamaño aparente del Sol, y lejos de ella la sombra se disuelve.
+fn caster_shadow(point: vec3<f32>) -> f32 {
+    if shadows.caster.z <= 0.0 {
+        return 1.0;
+    }
+    let clip = shadows.light_view_proj * vec4<f32>(point, 1.0);
+    let uv = clip.xy * vec2<f32>(0.5, -0.5) + 0.5;
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || clip.z < 0.0 {
+        return 1.0;
+    }
+    // Más allá de la caja la sombra continúa: la profundidad se satura en el fondo
+    let depth = min(clip.z, 1.0);
+    let size = vec2<f32>(textureDimensions(shadow_map));
+
+    // Bloqueador más cercano al Sol en los alrededores del punto
+    let texel = vec2<i32>(uv * size);
+    let last = vec2<i32>(size) - 1;
+    var blocker = textureLoad(shadow_map, clamp(texel, vec2<i32>(0), last), 0);
+    for (var i = 0; i < 4; i++) {
+        let corner = vec2<i32>(select(-8, 8, (i & 1) == 1), select(-8, 8, (i & 2) == 2));
+        blocker = min(blocker, textureLoad(shadow_map, clamp(texel + corner, vec2<i32>(0), last), 0));
+    }
+    if blocker >= depth {
+        return 1.0;
+    }
+
+    let behind = (depth - blocker) * shadows.caster.y;
+    let sun_radius = asin(min(shadows.sun.w / max(distance(shadows.sun.xyz, point), 1e-4), 1.0));
+    let penumbra = behind * 2.0 * tan(sun_radius);
+    let texel_size = 2.0 * shadows.caster.x / size.x;
+    let kernel = clamp(penumbra / texel_size, 1.0, 12.0) / size;
+
+    // PCF de 4x4 muestras sobre la penumbra
+    var lit = 0.0;
+    for (var y = 0; y < 4; y++) {
+        for (var x = 0; x < 4; x++) {
+            let offset = (vec2<f32>(f32(x), f32(y)) - 1.5) / 1.5 * kernel;
+            lit += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, depth - 0.002);
+        }
+    }
+    lit /= 16.0;
+
+    let strength = shadows.caster.z * shadows.caster.x / (shadows.caster.x + penumbra);
+    return 1.0 - (1.0 - lit) * strength;
+}
+
+// Fracción de la luz del Sol que llega a `point`
+fn sun_visibility(point: vec3<f32>) -> f32 {
+    return body_shadow(point) * ring_shadow(point) * caster_shadow(point);
+}
@@ -1,35 +1,32 @@
-[[group(0), binding(0)]] var skybox_texture: texture_cube<f32>;
-[[group(0), binding(1)]] var skybox_sampler: sampler;
+struct SkyboxUniforms {
+    inv_view_proj: mat4x4<f32>, // Inversa de la vista (solo rotación) por la proyección
+};
+
+@group(0) @binding(0) var<uniform> sky: SkyboxUniforms;
+@group(0) @binding(1) var skybox_texture: texture_cube<f32>;
+@group(0) @binding(2) var skybox_sampler: sampler;
 
 struct VertexOutput {
-    [[builtin(position)]] position: vec4<f32>;
-    [[location(0)]] tex_coords: vec3<f32>;
+    @builtin(position) position: vec4<f32>,
+    @location(0) clip: vec2<f32>,
 };
 
-[[stage(vertex)]]
-fn vs_main([[builtin(vertex_index)]] vertex_index: u32) -> VertexOutput {
-    var positions = array<vec3<f32>, 36>(
-        vec3(-1.0, -1.0, -1.0), vec3(1.0, -1.0, -1.0), vec3(1.0,  1.0, -1.0),
-        vec3(-1.0, -1.0, -1.0), vec3(1.0,  1.0, -1.0), vec3(-1.0,  1.0, -1.0),
-        vec3(-1.0, -1.0,  1.0), vec3(1.0, -1.0,  1.0), vec3(1.0,  1.0,  1.0),
-        vec3(-1.0, -1.0,  1.0), vec3(1.0,  1.0,  1.0), vec3(-1.0,  1.0,  1.0),
-        vec3(-1.0, -1.0, -1.0), vec3(-1.0, -1.0,  1.0), vec3(-1.0,  1.0,  1.0),
-        vec3(-1.0, -1.0, -1.0), vec3(-1.0,  1.0,  1.0), vec3(-1.0,  1.0, -1.0),
-        vec3(1.0, -1.0, -1.0), vec3(1.0, -1.0,  1.0), vec3(1.0,  1.0,  1.0),
-        vec3(1.0, -1.0, -1.0), vec3(1.0,  1.0,  1.0), vec3(1.0,  1.0, -1.0),
-        vec3(-1.0, -1.0, -1.0), vec3(-1.0, -1.0,  1.0), vec3(1.0, -1.0,  1.0),
-        vec3(-1.0, -1.0, -1.0), vec3(1.0, -1.0,  1.0), vec3(1.0, -1.0, -1.0),
-        vec3(-1.0,  1.0, -1.0), vec3(-1.0,  1.0,  1.0), vec3(1.0,  1.0,  1.0),
-        vec3(-1.0,  1.0, -1.0), vec3(1.0,  1.0,  1.0), vec3(1.0,  1.0, -1.0)
-    );
-    let position = positions[vertex_index];
+// Un único triángulo que cubre toda la pantalla
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    let clip = uv * 2.0 - 1.0;
+
     var output: VertexOutput;
-    output.position = vec4<f32>(position, 1.0);
-    output.tex_coords = position;
+    output.position = vec4<f32>(clip, 1.0, 1.0);
+    output.clip = clip;
     return output;
 }
 
-[[stage(fragment)]]
-fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
-    return textureSample(skybox_texture, skybox_sampler, input.tex_coords);
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    // Dirección de visión reconstruida desde la posición en pantalla
+    let world = sky.inv_view_proj * vec4<f32>(input.clip, 1.0, 1.0);
+    let direction = normalize(world.xyz / world.w);
+    return vec4<f32>(textureSample(skybox_texture, skybox_sampler, direction).rgb, 1.0);
 }
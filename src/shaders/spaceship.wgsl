@@ -1,29 +1,48 @@
-struct Uniforms {
-    view_proj: mat4x4<f32>,
-    model: mat4x4<f32>,
-    color: vec4<f32>,
-    time: f32,
-    orbital_radius: f32,
-    orbital_speed: f32,
-};
+// Superficie rocosa usada por la nave
 
-@group(0) @binding(0)
-var<uniform> uniforms: Uniforms;
+#include "common.wgsl"
+#include "lighting.wgsl"
+#include "shadows.wgsl"
 
 struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) world_normal: vec3<f32>,
+    @location(3) world_position: vec3<f32>,
 };
 
 @vertex
 fn vs_main(@location(0) position: vec3<f32>) -> VertexOutput {
+    let world = uniforms.model * vec4<f32>(position, 1.0);
     var out: VertexOutput;
-    out.clip_position = uniforms.view_proj * uniforms.model * vec4<f32>(position, 1.0);
+    out.position = uniforms.view_proj * world;
+    out.normal = normalize(position);
     out.color = uniforms.color;
+    out.world_normal = (uniforms.model * vec4<f32>(out.normal, 0.0)).xyz;
+    out.world_position = world.xyz;
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
-}
\ No newline at end of file
+    let light_dir = sun_direction(in.world_position);
+    let view_dir = normalize(uniforms.camera_position.xyz - in.world_position);
+    let shadow = sun_visibility(in.world_position);
+    let diffuse = lambert(in.world_normal, light_dir) * shadow;
+    let ambient = 0.2;
+
+    // Reflejo intenso (por encima de 1 en HDR) que genera bloom
+    let highlight = blinn_phong(in.world_normal, light_dir, view_dir, 64.0) * 3.0 * shadow;
+
+    let vignette = 1.0 - length(in.normal.xy);
+    let rock_pattern = sin(in.normal.x * 8.0 + in.normal.z * 8.0) *
+                       cos(in.normal.y * 10.0 + in.normal.z * 10.0);
+    let rock_variation = smoothstep(-0.3, 0.3, rock_pattern);
+
+    let base_rock_color = vec3<f32>(0.4, 0.3, 0.2);
+    let highlighted_rock_color = vec3<f32>(0.7, 0.6, 0.5);
+    let rock_color = mix(base_rock_color, highlighted_rock_color, rock_variation);
+
+    return vec4<f32>((ambient + diffuse * vignette) * rock_color + vec3<f32>(highlight), in.color.a);
+}
@@ -0,0 +1,96 @@
+// Campo de estrellas. Con STAR_BUFFER las posiciones llegan del respaldo en CPU;
+// sin ella se generan en el vertex shader a partir de la semilla.
+
+struct StarUniforms {
+    view_proj: mat4x4<f32>,
+    seed: u32,
+    count: u32,
+    min_radius: f32,
+    max_radius: f32,
+    band_fraction: f32,
+    band_width: f32,     // Radianes
+    galactic_tilt: f32,  // Radianes
+    core_longitude: f32, // Radianes
+};
+@group(0) @binding(0) var<uniform> stars: StarUniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) brightness: f32,
+};
+
+// Hash PCG: debe coincidir con `stars::pcg_hash` para que el respaldo en CPU
+// produzca exactamente el mismo cielo.
+fn pcg_hash(value: u32) -> u32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn unit_float(hash: u32) -> f32 {
+    return f32(hash) / 4294967295.0;
+}
+
+fn star_output(position: vec3<f32>, brightness: f32) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = stars.view_proj * vec4<f32>(position, 1.0);
+    out.brightness = brightness;
+    return out;
+}
+
+// Dirección de la estrella: una parte se concentra en la banda galáctica y el resto
+// se reparte de forma uniforme. Debe coincidir con `stars::star_direction`.
+fn star_direction(h0: u32, h1: u32, h2: u32, h3: u32) -> vec3<f32> {
+    if unit_float(h0) >= stars.band_fraction {
+        let theta = unit_float(h1) * 6.2831853;
+        let z = unit_float(h2) * 2.0 - 1.0;
+        let ring = sqrt(max(1.0 - z * z, 0.0));
+        return vec3<f32>(ring * cos(theta), ring * sin(theta), z);
+    }
+
+    // Latitud gaussiana (Box-Muller) y longitud concentrada hacia el núcleo
+    let gaussian = sqrt(-2.0 * log(max(unit_float(h1), 1e-7))) * cos(unit_float(h2) * 6.2831853);
+    let latitude = gaussian * stars.band_width;
+    let offset = unit_float(h3) * 2.0 - 1.0;
+    let longitude = stars.core_longitude + 3.1415927 * offset * offset * offset;
+
+    let galactic = vec3<f32>(
+        cos(latitude) * cos(longitude),
+        sin(latitude),
+        cos(latitude) * sin(longitude),
+    );
+
+    // Inclinación del plano galáctico alrededor del eje X
+    let c = cos(stars.galactic_tilt);
+    let s = sin(stars.galactic_tilt);
+    return vec3<f32>(galactic.x, galactic.y * c - galactic.z * s, galactic.y * s + galactic.z * c);
+}
+
+#ifdef STAR_BUFFER
+// Respaldo en CPU: posición en xyz y brillo en w
+@vertex
+fn vs_main(@location(0) star: vec4<f32>) -> VertexOutput {
+    return star_output(star.xyz, star.w);
+}
+#else
+// Camino principal: la posición se deriva del índice del vértice, sin vertex buffer
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let h0 = pcg_hash(index ^ pcg_hash(stars.seed));
+    let h1 = pcg_hash(h0);
+    let h2 = pcg_hash(h1);
+    let h3 = pcg_hash(h2);
+    let h4 = pcg_hash(h3);
+    let h5 = pcg_hash(h4);
+
+    let radius = mix(stars.min_radius, stars.max_radius, unit_float(h4));
+    let position = star_direction(h0, h1, h2, h3) * radius;
+
+    return star_output(position, mix(0.5, 1.0, unit_float(h5)));
+}
+#endif
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(vec3<f32>(in.brightness), 1.0);
+}
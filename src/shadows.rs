@@ -0,0 +1,322 @@
+// Sombras del Sol: los cuerpos y los anillos se describen de forma analítica (esferas y
+// discos) en un bloque de uniforms compartido, y las mallas como la nave se dibujan en un
+// shadow map ortográfico visto desde el Sol que sigue a la malla.
+
+use bytemuck::Zeroable;
+use cgmath::{InnerSpace, Matrix4, Point3, Vector3};
+use wgpu::util::DeviceExt;
+
+use crate::shaders;
+
+// Deben coincidir con las constantes de shadows.wgsl
+pub const MAX_OCCLUDERS: usize = 32;
+pub const MAX_RING_OCCLUDERS: usize = 4;
+
+const SHADOW_MAP_SIZE: u32 = 2048;
+// Distancia detrás de la malla que cubre el shadow map; más allá la sombra se prolonga
+const SHADOW_DEPTH: f32 = 60.0;
+
+// cgmath produce profundidades en [-1, 1] como OpenGL; wgpu las espera en [0, 1]
+#[rustfmt::skip]
+const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+// Anillo visto como un disco translúcido que tapa el Sol
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RingOccluder {
+    center: [f32; 4], // Centro (xyz) y opacidad (w)
+    axis: [f32; 4],   // Normal del plano (xyz) y cantidad de bandas (w)
+    inner_radius: f32,
+    outer_radius: f32,
+    seed: u32,
+    _padding: u32,
+}
+
+impl RingOccluder {
+    // Radios en unidades del mundo; la densidad usa el mismo ruido que el shader del anillo
+    pub fn new(inner_radius: f32, outer_radius: f32, opacity: f32, band_count: f32, seed: u32) -> Self {
+        Self {
+            center: [0.0, 0.0, 0.0, opacity],
+            axis: [0.0, 1.0, 0.0, band_count],
+            inner_radius,
+            outer_radius,
+            seed,
+            _padding: 0,
+        }
+    }
+
+    pub fn place(&mut self, center: Vector3<f32>, axis: Vector3<f32>) {
+        self.center = [center.x, center.y, center.z, self.center[3]];
+        self.axis = [axis.x, axis.y, axis.z, self.axis[3]];
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowUniforms {
+    light_view_proj: [[f32; 4]; 4],
+    sun: [f32; 4],    // Centro (xyz) y radio (w)
+    caster: [f32; 4], // Medio ancho de la caja, profundidad que cubre, intensidad y relleno
+    occluder_count: u32,
+    ring_count: u32,
+    _padding: [u32; 2],
+    occluders: [[f32; 4]; MAX_OCCLUDERS], // Centro (xyz) y radio (w) de cada cuerpo
+    rings: [RingOccluder; MAX_RING_OCCLUDERS],
+}
+
+pub struct Shadows {
+    uniforms: ShadowUniforms,
+    uniform_buffer: wgpu::Buffer,
+    pub shadow_view: wgpu::TextureView,
+    // Grupo 1 de los pipelines que reciben sombras
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+    // Pipeline de profundidad de las mallas: grupo 0 = uniforms de la malla, grupo 1 = luz
+    pub caster_pipeline: wgpu::RenderPipeline,
+    caster_layout: wgpu::BindGroupLayout,
+    pub light_bind_group: wgpu::BindGroup,
+}
+
+impl Shadows {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let uniforms = ShadowUniforms {
+            light_view_proj: Matrix4::from_scale(1.0).into(),
+            sun: [0.0, 0.0, 0.0, 1.0],
+            caster: [1.0, SHADOW_DEPTH, 0.0, 0.0],
+            occluder_count: 0,
+            ring_count: 0,
+            _padding: [0; 2],
+            occluders: [[0.0; 4]; MAX_OCCLUDERS],
+            rings: [RingOccluder::zeroed(); MAX_RING_OCCLUDERS],
+        };
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Shadow Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let shadow_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Map"),
+            size: wgpu::Extent3d {
+                width: SHADOW_MAP_SIZE,
+                height: SHADOW_MAP_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let shadow_view = shadow_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Comparación con filtrado lineal: cada muestra ya suaviza el borde entre texels
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let uniform_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Bind Group Layout"),
+            entries: &[
+                uniform_entry,
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&shadow_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        // El pase del shadow map no puede leer la textura en la que escribe: solo la luz
+        let caster_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Caster Bind Group Layout"),
+            entries: &[uniform_entry],
+        });
+        let light_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Light Bind Group"),
+            layout: &caster_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shadow Caster Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("shadow_caster.wgsl", &[]).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Caster Pipeline Layout"),
+            bind_group_layouts: &[&caster_layout, &caster_layout],
+            push_constant_ranges: &[],
+        });
+
+        let caster_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shadow Caster Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<[f32; 3]>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                }],
+            },
+            fragment: None, // Solo profundidad
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None, // La malla de la nave no es cerrada
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                // Evita que la malla se sombree a sí misma por la precisión del mapa
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            uniforms,
+            uniform_buffer,
+            shadow_view,
+            bind_group_layout,
+            bind_group,
+            caster_pipeline,
+            caster_layout,
+            light_bind_group,
+        }
+    }
+
+    // Grupo 0 del pipeline del shadow map para una malla: sus uniforms (la matriz del modelo)
+    pub fn caster_bind_group(&self, device: &wgpu::Device, uniform_buffer: &wgpu::Buffer) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Caster Bind Group"),
+            layout: &self.caster_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        })
+    }
+
+    // El Sol y los cuerpos que pueden taparlo: esferas (centro y radio) y anillos
+    pub fn set_bodies(&mut self, sun: Vector3<f32>, sun_radius: f32, spheres: &[[f32; 4]], rings: &[RingOccluder]) {
+        self.uniforms.sun = [sun.x, sun.y, sun.z, sun_radius];
+
+        let spheres = &spheres[..spheres.len().min(MAX_OCCLUDERS)];
+        self.uniforms.occluders[..spheres.len()].copy_from_slice(spheres);
+        self.uniforms.occluder_count = spheres.len() as u32;
+
+        let rings = &rings[..rings.len().min(MAX_RING_OCCLUDERS)];
+        self.uniforms.rings[..rings.len()].copy_from_slice(rings);
+        self.uniforms.ring_count = rings.len() as u32;
+    }
+
+    // Orienta el shadow map hacia la malla que proyecta sombra. `radius` la encierra y
+    // `strength` atenúa la sombra (la nave se desvanece durante el warping).
+    pub fn set_caster(&mut self, position: Vector3<f32>, radius: f32, strength: f32) {
+        let sun = Vector3::new(self.uniforms.sun[0], self.uniforms.sun[1], self.uniforms.sun[2]);
+        let offset = position - sun;
+        let direction = if offset.magnitude2() > 0.0 {
+            offset.normalize()
+        } else {
+            Vector3::unit_z()
+        };
+        let up = if direction.y.abs() < 0.99 {
+            Vector3::unit_y()
+        } else {
+            Vector3::unit_x()
+        };
+
+        // Caja ortográfica que empieza justo antes de la malla y se extiende detrás de ella
+        let eye = position - direction * radius;
+        let view = Matrix4::look_at_rh(
+            Point3::new(eye.x, eye.y, eye.z),
+            Point3::new(position.x, position.y, position.z),
+            up,
+        );
+        let depth = radius + SHADOW_DEPTH;
+        let proj = OPENGL_TO_WGPU_MATRIX * cgmath::ortho(-radius, radius, -radius, radius, 0.0, depth);
+
+        self.uniforms.light_view_proj = (proj * view).into();
+        self.uniforms.caster = [radius, depth, strength.clamp(0.0, 1.0), 0.0];
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniforms]));
+    }
+}
@@ -0,0 +1,409 @@
+use std::path::Path;
+
+use cgmath::SquareMatrix;
+use wgpu::util::DeviceExt;
+
+use crate::shaders;
+
+// Orden de las caras de un cubemap en wgpu: +X, -X, +Y, -Y, +Z, -Z
+const FACE_NAMES: [&str; 6] = ["px", "nx", "py", "ny", "pz", "nz"];
+const FACE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct SkyboxUniforms {
+    inv_view_proj: [[f32; 4]; 4],
+}
+
+// Dirección en el mundo del píxel (u, v) de una cara, con u y v en [-1, 1]
+pub fn cube_face_direction(face: usize, u: f32, v: f32) -> [f32; 3] {
+    match face {
+        0 => [1.0, -v, -u],  // +X
+        1 => [-1.0, -v, u],  // -X
+        2 => [u, 1.0, v],    // +Y
+        3 => [u, -1.0, -v],  // -Y
+        4 => [u, -v, 1.0],   // +Z
+        _ => [-u, -v, -1.0], // -Z
+    }
+}
+
+// Píxeles de las seis caras listos para subir a la GPU
+pub struct CubemapImage {
+    size: u32,
+    format: wgpu::TextureFormat,
+    bytes_per_pixel: u32,
+    faces: Vec<Vec<u8>>,
+}
+
+impl CubemapImage {
+    // Acepta una carpeta con px/nx/py/ny/pz/nz o una imagen equirectangular (.hdr, .png, .jpg);
+    // `max_size` es el lado máximo de una cara que admite el dispositivo
+    pub fn load(path: &Path, max_size: u32) -> Result<Self, String> {
+        if path.is_dir() {
+            Self::load_faces(path, max_size)
+        } else {
+            Self::load_equirectangular(path, max_size)
+        }
+    }
+
+    fn load_faces(directory: &Path, max_size: u32) -> Result<Self, String> {
+        let mut faces = Vec::new();
+        let mut size = 0;
+
+        for name in FACE_NAMES {
+            let face_path = FACE_EXTENSIONS
+                .iter()
+                .map(|extension| directory.join(format!("{}.{}", name, extension)))
+                .find(|candidate| candidate.exists())
+                .ok_or_else(|| format!("Falta la cara '{}' del skybox en {}", name, directory.display()))?;
+
+            let face = image::open(&face_path)
+                .map_err(|error| format!("No se pudo leer {}: {}", face_path.display(), error))?
+                .to_rgba8();
+
+            if face.width() != face.height() || (size != 0 && face.width() != size) {
+                return Err(format!(
+                    "Las caras del skybox deben ser cuadradas y del mismo tamaño ({})",
+                    face_path.display()
+                ));
+            }
+            if face.width() > max_size {
+                return Err(format!(
+                    "Las caras del skybox miden {} px y el dispositivo admite hasta {} ({})",
+                    face.width(),
+                    max_size,
+                    face_path.display()
+                ));
+            }
+            size = face.width();
+            faces.push(face.into_raw());
+        }
+
+        Ok(Self {
+            size,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            bytes_per_pixel: 4,
+            faces,
+        })
+    }
+
+    fn load_equirectangular(path: &Path, max_size: u32) -> Result<Self, String> {
+        let panorama = image::open(path)
+            .map_err(|error| format!("No se pudo leer {}: {}", path.display(), error))?
+            .to_rgba32f();
+        let (width, height) = panorama.dimensions();
+        if width == 0 || height < 2 {
+            return Err(format!("El panorama {} es demasiado pequeño ({}x{})", path.display(), width, height));
+        }
+        Ok(Self::from_panorama(&panorama, max_size))
+    }
+
+    // Proyecta un panorama equirectangular (de al menos 1x2) sobre las seis caras
+    fn from_panorama(panorama: &image::Rgba32FImage, max_size: u32) -> Self {
+        let (width, height) = panorama.dimensions();
+        // Se remuestrea, así que basta con limitar el lado de las caras
+        let size = (height / 2).min(max_size);
+
+        // Muestreo bilineal del panorama con envoltura horizontal
+        let sample = |x: f32, y: f32| -> [f32; 4] {
+            let x = x * width as f32 - 0.5;
+            let y = (y * height as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+            let (x0, y0) = (x.floor(), y.floor());
+            let (tx, ty) = (x - x0, y - y0);
+            let wrap = |x: f32| (x as i64).rem_euclid(width as i64) as u32;
+            let row = |y: f32| (y as u32).min(height - 1);
+
+            let texel = |x: f32, y: f32| panorama.get_pixel(wrap(x), row(y)).0;
+            let (a, b) = (texel(x0, y0), texel(x0 + 1.0, y0));
+            let (c, d) = (texel(x0, y0 + 1.0), texel(x0 + 1.0, y0 + 1.0));
+
+            let mut result = [0.0; 4];
+            for i in 0..4 {
+                let top = a[i] + (b[i] - a[i]) * tx;
+                let bottom = c[i] + (d[i] - c[i]) * tx;
+                result[i] = top + (bottom - top) * ty;
+            }
+            result
+        };
+
+        let faces = (0..6)
+            .map(|face| {
+                let mut bytes = Vec::with_capacity((size * size * 8) as usize);
+                for py in 0..size {
+                    for px in 0..size {
+                        let u = (px as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                        let v = (py as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                        let [x, y, z] = cube_face_direction(face, u, v);
+                        let length = (x * x + y * y + z * z).sqrt();
+
+                        let longitude = x.atan2(-z);
+                        let latitude = (y / length).asin();
+                        let texel = sample(
+                            longitude / std::f32::consts::TAU + 0.5,
+                            0.5 - latitude / std::f32::consts::PI,
+                        );
+
+                        for channel in texel {
+                            bytes.extend_from_slice(&half::f16::from_f32(channel).to_bits().to_le_bytes());
+                        }
+                    }
+                }
+                bytes
+            })
+            .collect();
+
+        Self {
+            size,
+            format: wgpu::TextureFormat::Rgba16Float,
+            bytes_per_pixel: 8,
+            faces,
+        }
+    }
+
+    pub fn create_texture(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Skybox Cubemap"),
+            size: wgpu::Extent3d {
+                width: self.size,
+                height: self.size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        for (layer, face) in self.faces.iter().enumerate() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                face,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.size * self.bytes_per_pixel),
+                    rows_per_image: Some(self.size),
+                },
+                wgpu::Extent3d {
+                    width: self.size,
+                    height: self.size,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        texture
+    }
+}
+
+pub struct Skybox {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    _cubemap: wgpu::Texture, // Se conserva mientras el bind group usa su vista
+}
+
+impl Skybox {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32, cubemap: wgpu::Texture) -> Self {
+        let cubemap_view = cubemap.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Skybox Cubemap View"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        let uniforms = SkyboxUniforms {
+            inv_view_proj: cgmath::Matrix4::identity().into(),
+        };
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Skybox Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Skybox Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Skybox Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Skybox Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&cubemap_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Skybox Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("skybox.wgsl", &[]).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Skybox Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Skybox Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            uniform_buffer,
+            bind_group,
+            _cubemap: cubemap,
+        }
+    }
+
+    // Recibe la misma matriz que las estrellas: proyección por vista sin traslación
+    pub fn update(&self, queue: &wgpu::Queue, sky_view_proj: cgmath::Matrix4<f32>) {
+        let inverse = sky_view_proj.invert().unwrap_or_else(cgmath::Matrix4::identity);
+        let uniforms = SkyboxUniforms {
+            inv_view_proj: inverse.into(),
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_centres_point_along_their_axis() {
+        let axes = [
+            [1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, -1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, -1.0],
+        ];
+        for (face, axis) in axes.iter().enumerate() {
+            assert_eq!(cube_face_direction(face, 0.0, 0.0), *axis, "cara {}", FACE_NAMES[face]);
+        }
+    }
+
+    // Color en half float del píxel central de una cara
+    fn centre(image: &CubemapImage, face: usize) -> [f32; 3] {
+        let offset = ((image.size / 2 * image.size + image.size / 2) * image.bytes_per_pixel) as usize;
+        let bytes = &image.faces[face][offset..offset + 6];
+        let channel = |i: usize| half::f16::from_bits(u16::from_le_bytes([bytes[i * 2], bytes[i * 2 + 1]])).to_f32();
+        [channel(0), channel(1), channel(2)]
+    }
+
+    #[test]
+    fn panorama_keeps_poles_and_equator() {
+        // Norte rojo, ecuador verde y sur azul, en franjas horizontales
+        let panorama = image::Rgba32FImage::from_fn(32, 16, |_, y| match y {
+            0..=3 => image::Rgba([1.0, 0.0, 0.0, 1.0]),
+            12..=15 => image::Rgba([0.0, 0.0, 1.0, 1.0]),
+            _ => image::Rgba([0.0, 1.0, 0.0, 1.0]),
+        });
+        let image = CubemapImage::from_panorama(&panorama, 4096);
+        assert_eq!(image.size, 8);
+        assert_eq!(centre(&image, 2), [1.0, 0.0, 0.0]); // +Y
+        assert_eq!(centre(&image, 3), [0.0, 0.0, 1.0]); // -Y
+        for face in [0, 1, 4, 5] {
+            assert_eq!(centre(&image, face), [0.0, 1.0, 0.0], "cara {}", FACE_NAMES[face]);
+        }
+        assert_eq!(CubemapImage::from_panorama(&panorama, 4).size, 4);
+    }
+}
@@ -0,0 +1,315 @@
+use wgpu::util::DeviceExt;
+
+use crate::scene::BackgroundParams;
+use crate::settings::{Settings, StarGeneration};
+use crate::shaders;
+
+// Radio mínimo y máximo de la cáscara de estrellas
+const STAR_MIN_RADIUS: f32 = 5.0;
+const STAR_MAX_RADIUS: f32 = 20.0;
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct StarUniforms {
+    view_proj: [[f32; 4]; 4],
+    seed: u32,
+    count: u32,
+    min_radius: f32,
+    max_radius: f32,
+    band_fraction: f32,
+    band_width: f32,
+    galactic_tilt: f32,
+    core_longitude: f32,
+}
+
+struct Star {
+    position: [f32; 3],
+    brightness: f32,
+}
+
+// Mismo hash PCG que usa stars.wgsl en la GPU
+fn pcg_hash(value: u32) -> u32 {
+    let state = value.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    (word >> 22) ^ word
+}
+
+fn unit_float(hash: u32) -> f32 {
+    hash as f32 / u32::MAX as f32
+}
+
+// Réplica de `star_direction` en stars.wgsl
+fn star_direction(uniforms: &StarUniforms, h0: u32, h1: u32, h2: u32, h3: u32) -> [f32; 3] {
+    use std::f32::consts::{PI, TAU};
+
+    if unit_float(h0) >= uniforms.band_fraction {
+        let theta = unit_float(h1) * TAU; // Ángulo azimutal
+        let z = unit_float(h2) * 2.0 - 1.0; // Altura sobre la esfera unitaria
+        let ring = (1.0 - z * z).max(0.0).sqrt();
+        return [ring * theta.cos(), ring * theta.sin(), z];
+    }
+
+    // Latitud gaussiana (Box-Muller) y longitud concentrada hacia el núcleo
+    let gaussian = (-2.0 * unit_float(h1).max(1e-7).ln()).sqrt() * (unit_float(h2) * TAU).cos();
+    let latitude = gaussian * uniforms.band_width;
+    let offset = unit_float(h3) * 2.0 - 1.0;
+    let longitude = uniforms.core_longitude + PI * offset * offset * offset;
+
+    let galactic = [
+        latitude.cos() * longitude.cos(),
+        latitude.sin(),
+        latitude.cos() * longitude.sin(),
+    ];
+
+    // Inclinación del plano galáctico alrededor del eje X
+    let (s, c) = uniforms.galactic_tilt.sin_cos();
+    [galactic[0], galactic[1] * c - galactic[2] * s, galactic[1] * s + galactic[2] * c]
+}
+
+impl Star {
+    // Réplica de `vs_main` de stars.wgsl sin STAR_BUFFER: la estrella `index` es la misma en CPU y GPU
+    fn from_seed(uniforms: &StarUniforms, index: u32) -> Self {
+        let h0 = pcg_hash(index ^ pcg_hash(uniforms.seed));
+        let h1 = pcg_hash(h0);
+        let h2 = pcg_hash(h1);
+        let h3 = pcg_hash(h2);
+        let h4 = pcg_hash(h3);
+        let h5 = pcg_hash(h4);
+
+        let radius = STAR_MIN_RADIUS + (STAR_MAX_RADIUS - STAR_MIN_RADIUS) * unit_float(h4);
+        let [x, y, z] = star_direction(uniforms, h0, h1, h2, h3);
+
+        Self {
+            position: [radius * x, radius * y, radius * z],
+            brightness: 0.5 + 0.5 * unit_float(h5),
+        }
+    }
+}
+
+pub struct StarField {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: Option<wgpu::Buffer>, // Solo existe con el respaldo en CPU
+    num_stars: u32,
+    uniforms: StarUniforms,
+}
+
+impl StarField {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        settings: &Settings,
+        background: &BackgroundParams,
+    ) -> Self {
+        let uniforms = StarUniforms {
+            view_proj: cgmath::Matrix4::from_scale(1.0).into(),
+            seed: settings.star_seed.unwrap_or(background.seed),
+            count: settings.star_count,
+            min_radius: STAR_MIN_RADIUS,
+            max_radius: STAR_MAX_RADIUS,
+            band_fraction: background.band_fraction,
+            band_width: background.band_width.to_radians(),
+            galactic_tilt: background.galactic_tilt.to_radians(),
+            core_longitude: background.core_longitude.to_radians(),
+        };
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Star Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Star Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Star Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        // Con el respaldo en CPU se generan todas las estrellas antes de arrancar
+        let vertex_buffer = match settings.star_generation {
+            StarGeneration::Gpu => None,
+            StarGeneration::Cpu => {
+                let stars: Vec<[f32; 4]> = (0..settings.star_count)
+                    .map(|index| {
+                        let star = Star::from_seed(&uniforms, index);
+                        let [x, y, z] = star.position;
+                        [x, y, z, star.brightness]
+                    })
+                    .collect();
+
+                Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Star Buffer"),
+                    contents: bytemuck::cast_slice(&stars),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }))
+            }
+        };
+
+        let pipeline = Self::create_pipeline(device, format, sample_count, &bind_group_layout, settings.star_generation);
+
+        Self {
+            pipeline,
+            uniform_buffer,
+            bind_group,
+            vertex_buffer,
+            num_stars: settings.star_count,
+            uniforms,
+        }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        generation: StarGeneration,
+    ) -> wgpu::RenderPipeline {
+        // El respaldo en CPU lee las estrellas de un vertex buffer
+        let defines: &[&str] = match generation {
+            StarGeneration::Gpu => &[],
+            StarGeneration::Cpu => &["STAR_BUFFER"],
+        };
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Star Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("stars.wgsl", defines).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Star Pipeline Layout"),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let buffer_layout = [wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 4]>() as u64,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x4],
+        }];
+
+        let buffers: &[wgpu::VertexBufferLayout] = match generation {
+            StarGeneration::Gpu => &[],
+            StarGeneration::Cpu => &buffer_layout,
+        };
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Star Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::PointList, // Renderizar como puntos
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+
+    // Las estrellas siguen solo la rotación de la cámara, así rodean siempre al observador
+    pub fn update(&mut self, queue: &wgpu::Queue, sky_view_proj: cgmath::Matrix4<f32>) {
+        self.uniforms.view_proj = sky_view_proj.into();
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[self.uniforms]));
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        if let Some(vertex_buffer) = &self.vertex_buffer {
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        }
+        render_pass.draw(0..self.num_stars, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Valores de referencia de stars.wgsl, calculados transcribiendo el shader operación por
+    // operación en f32; el respaldo en CPU tiene que dibujar el mismo cielo
+    fn assert_close(actual: f32, expected: f32) {
+        let tolerance = 1e-4 * expected.abs().max(1.0);
+        assert!((actual - expected).abs() <= tolerance, "{} != {}", actual, expected);
+    }
+
+    fn uniforms(seed: u32) -> StarUniforms {
+        StarUniforms {
+            view_proj: cgmath::Matrix4::from_scale(1.0).into(),
+            seed,
+            count: 1000,
+            min_radius: STAR_MIN_RADIUS,
+            max_radius: STAR_MAX_RADIUS,
+            band_fraction: 0.35,
+            band_width: 12.0_f32.to_radians(),
+            galactic_tilt: 60.0_f32.to_radians(),
+            core_longitude: 30.0_f32.to_radians(),
+        }
+    }
+
+    #[test]
+    fn pcg_hash_matches_the_shader() {
+        let cases = [(0, 129708002), (1, 2831084092), (42, 1223963391), (0xdeadbeef, 1730779506)];
+        for (value, expected) in cases {
+            assert_eq!(pcg_hash(value), expected);
+        }
+    }
+
+    #[test]
+    fn stars_match_the_shader() {
+        // Índices que caen tanto en la distribución uniforme como en la banda galáctica
+        let cases = [
+            (7, 0, [10.6922, -10.4104, 1.80834], 0.553982),
+            (7, 3, [-1.50495, -6.47909, 2.7602], 0.601827),
+            (2024, 5, [14.9785, -2.86592, 6.52032], 0.501172),
+            (2024, 1000, [-3.89464, -7.02173, 7.32442], 0.633858),
+        ];
+        for (seed, index, position, brightness) in cases {
+            let star = Star::from_seed(&uniforms(seed), index);
+            for (actual, expected) in star.position.into_iter().zip(position) {
+                assert_close(actual, expected);
+            }
+            assert_close(star.brightness, brightness);
+        }
+    }
+}
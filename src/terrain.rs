@@ -0,0 +1,502 @@
+// Terreno de los cuerpos sólidos visto de cerca: cada cara de un cubo proyectado sobre la
+// esfera es la raíz de un quadtree de parches (chunks) con relieve. Los parches cercanos a
+// la cámara se subdividen, se generan a pedido unos pocos por frame y los que dejan de
+// usarse se descartan. La altura sale del mismo ruido con el que planet.wgsl pinta la superficie.
+
+use std::collections::HashMap;
+
+use cgmath::{InnerSpace, Vector3};
+use serde::Deserialize;
+use wgpu::util::DeviceExt;
+
+use crate::material::{MaterialKind, MaterialParams};
+use crate::noise;
+use crate::scene::BodyParams;
+use crate::shaders;
+
+// Debe coincidir con SEA_LEVEL de planet.wgsl
+const SEA_LEVEL: f32 = 0.52;
+
+// Cuadrados por lado de cada parche
+const CHUNK_RESOLUTION: usize = 16;
+
+// Un parche se subdivide cuando la cámara está a menos de esta cantidad de veces su tamaño
+const SPLIT_DISTANCE: f32 = 2.5;
+
+// Distancia al centro del cuerpo, en radios, por debajo de la cual se usa el terreno
+pub const TERRAIN_RANGE: f32 = 3.0;
+
+// Parches nuevos que se generan por frame; mientras tanto se dibuja el parche padre
+const CHUNKS_PER_FRAME: usize = 6;
+
+// Frames sin usarse tras los que un parche se descarta (las raíces se conservan)
+const EVICT_FRAMES: u64 = 300;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TerrainParams {
+    pub relief: f32,    // Altura de las montañas en radios del cuerpo
+    pub max_depth: u32, // Subdivisiones máximas de cada cara del cubo
+}
+
+impl Default for TerrainParams {
+    fn default() -> Self {
+        Self {
+            relief: 0.01,
+            max_depth: 10,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TerrainVertex {
+    position: [f32; 3], // En el marco del cuerpo, en radios
+    normal: [f32; 3],
+}
+
+// Normal de cada cara del cubo y sus ejes u, v (u × v = normal, así los triángulos miran afuera)
+const FACES: [[Vector3<f32>; 3]; 6] = [
+    [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0), Vector3::new(0.0, 1.0, 0.0)],
+    [Vector3::new(-1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 1.0, 0.0)],
+    [Vector3::new(0.0, 1.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0)],
+    [Vector3::new(0.0, -1.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0)],
+    [Vector3::new(0.0, 0.0, 1.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)],
+    [Vector3::new(0.0, 0.0, -1.0), Vector3::new(-1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)],
+];
+
+// Parche `(x, y)` de los 2^level × 2^level en que se divide una cara
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct ChunkKey {
+    face: u8,
+    level: u8,
+    x: u32,
+    y: u32,
+}
+
+impl ChunkKey {
+    fn root(face: u8) -> Self {
+        Self { face, level: 0, x: 0, y: 0 }
+    }
+
+    fn children(self) -> [Self; 4] {
+        let child = |dx, dy| Self {
+            face: self.face,
+            level: self.level + 1,
+            x: self.x * 2 + dx,
+            y: self.y * 2 + dy,
+        };
+        [child(0, 0), child(1, 0), child(0, 1), child(1, 1)]
+    }
+
+    // Lado del parche en coordenadas de la cara, que van de -1 a 1
+    fn size(self) -> f32 {
+        2.0 / (1u32 << self.level) as f32
+    }
+
+    // Ángulo aproximado que abarca el parche visto desde el centro del cuerpo
+    fn angular_size(self) -> f32 {
+        std::f32::consts::FRAC_PI_2 / (1u32 << self.level) as f32
+    }
+
+    // Dirección desde el centro del cuerpo al punto `(a, b)` de la cara
+    fn direction(self, a: f32, b: f32) -> Vector3<f32> {
+        let [normal, u, v] = FACES[self.face as usize];
+        (normal + u * a + v * b).normalize()
+    }
+
+    fn center(self) -> Vector3<f32> {
+        let size = self.size();
+        self.direction(-1.0 + (self.x as f32 + 0.5) * size, -1.0 + (self.y as f32 + 0.5) * size)
+    }
+}
+
+// Radio de la superficie en la dirección unitaria `p`, en radios del cuerpo. Sigue a
+// rocky_surface y ocean_elevation de planet.wgsl: continentes y cráteres en los mundos
+// rocosos; tierra firme con cordilleras sobre un mar plano en los oceánicos.
+pub fn surface_radius(material: &MaterialParams, relief: f32, p: Vector3<f32>) -> f32 {
+    let seed = noise::seed_offset(material.seed);
+    let height = match material.kind {
+        MaterialKind::Ocean => {
+            let elevation = noise::fbm3(noise::domain_warp3(p * material.noise_frequency + seed, 0.8), 6) * 0.5 + 0.5;
+            let land = ((elevation - SEA_LEVEL) / (1.0 - SEA_LEVEL)).max(0.0);
+            let ridges = noise::ridged3(p * material.detail_frequency + seed, 5);
+            land * (0.5 + ridges)
+        }
+        _ => {
+            let continents =
+                noise::fbm3(noise::domain_warp3(p * material.noise_frequency + seed, 0.7), 5) * 0.5 + 0.5;
+            let (cells, _) = noise::worley3(p * material.detail_frequency + seed);
+            let crater_floor = 1.0 - noise::smoothstep(0.15, 0.3, cells);
+            let crater_rim = noise::smoothstep(0.25, 0.32, cells) * (1.0 - noise::smoothstep(0.32, 0.42, cells));
+            (continents - 0.5) * 2.0 + crater_rim * 0.3 - crater_floor * 0.4
+        }
+    };
+    1.0 + relief * height
+}
+
+// Vértices de un parche: una grilla de (n + 1)² puntos con relieve y, alrededor, un faldón
+// que baja hacia el centro y tapa las grietas con los parches vecinos de otro nivel
+fn generate_chunk(key: ChunkKey, material: &MaterialParams, relief: f32) -> Vec<TerrainVertex> {
+    let n = CHUNK_RESOLUTION;
+    let step = key.size() / n as f32;
+    let (a0, b0) = (-1.0 + key.x as f32 * key.size(), -1.0 + key.y as f32 * key.size());
+
+    // Posiciones con un borde extra de un punto para calcular las normales en los lados
+    let stride = n + 3;
+    let mut grid = Vec::with_capacity(stride * stride);
+    for j in 0..stride {
+        for i in 0..stride {
+            let direction = key.direction(a0 + (i as f32 - 1.0) * step, b0 + (j as f32 - 1.0) * step);
+            grid.push(direction * surface_radius(material, relief, direction));
+        }
+    }
+    let at = |i: usize, j: usize| grid[(j + 1) * stride + i + 1];
+
+    let mut vertices = Vec::with_capacity((n + 1) * (n + 5));
+    for j in 0..=n {
+        for i in 0..=n {
+            let du = grid[(j + 1) * stride + i + 2] - grid[(j + 1) * stride + i];
+            let dv = grid[(j + 2) * stride + i + 1] - grid[j * stride + i + 1];
+            vertices.push(TerrainVertex {
+                position: at(i, j).into(),
+                normal: du.cross(dv).normalize().into(),
+            });
+        }
+    }
+
+    let skirt = 1.0 - relief.max(1e-3) * 2.0;
+    for (i, j) in skirt_border(n) {
+        let vertex = vertices[j * (n + 1) + i];
+        vertices.push(TerrainVertex {
+            position: (Vector3::from(vertex.position) * skirt).into(),
+            normal: vertex.normal,
+        });
+    }
+    vertices
+}
+
+// Puntos del borde de la grilla en el orden de los faldones: abajo, arriba, izquierda, derecha
+fn skirt_border(n: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..=n)
+        .map(|i| (i, 0))
+        .chain((0..=n).map(move |i| (i, n)))
+        .chain((0..=n).map(|j| (0, j)))
+        .chain((0..=n).map(move |j| (n, j)))
+}
+
+// Índices compartidos por todos los parches; los faldones se ven por ambas caras
+fn chunk_indices() -> Vec<u16> {
+    let n = CHUNK_RESOLUTION;
+    let row = n + 1;
+    let mut indices = Vec::new();
+    for j in 0..n {
+        for i in 0..n {
+            let corner = (j * row + i) as u16;
+            let (right, up) = (corner + 1, corner + row as u16);
+            indices.extend_from_slice(&[corner, right, up, right, up + 1, up]);
+        }
+    }
+    for edge in 0..4 {
+        let skirt = row * row + edge * row;
+        let border: Vec<(usize, usize)> = skirt_border(n).skip(edge * row).take(row).collect();
+        for k in 0..n {
+            let top = [border[k].1 * row + border[k].0, border[k + 1].1 * row + border[k + 1].0];
+            let bottom = [skirt + k, skirt + k + 1];
+            let [a, b, c, d] = [top[0], top[1], bottom[0], bottom[1]].map(|index| index as u16);
+            indices.extend_from_slice(&[a, b, c, b, d, c, a, c, b, b, c, d]);
+        }
+    }
+    indices
+}
+
+// Pipeline de los parches: el mismo material de planet.wgsl con normales del relieve
+pub struct TerrainPipeline {
+    pub pipeline: wgpu::RenderPipeline,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
+impl TerrainPipeline {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        uniform_layout: &wgpu::BindGroupLayout,
+        shadow_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Terrain Shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::compose("planet.wgsl", &["TERRAIN"]).into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Terrain Pipeline Layout"),
+            bind_group_layouts: &[uniform_layout, shadow_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Terrain Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<TerrainVertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent::REPLACE,
+                        alpha: wgpu::BlendComponent::REPLACE,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let indices = chunk_indices();
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Terrain Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            pipeline,
+            index_buffer,
+            num_indices: indices.len() as u32,
+        }
+    }
+}
+
+// Estado del quadtree, separado de los buffers: qué parches existen, cuándo se usaron y
+// cuáles se dibujan este frame
+struct Quadtree {
+    relief: f32,
+    max_depth: u32,
+    last_used: HashMap<ChunkKey, u64>, // Parches generados y el último frame en que se recorrieron
+    visible: Vec<ChunkKey>,
+}
+
+impl Quadtree {
+    // Recorre el quadtree: un parche se reemplaza por sus hijos solo cuando los cuatro existen;
+    // si falta alguno se dibuja el padre y se piden los hijos que faltan
+    fn select(&mut self, key: ChunkKey, eye: Vector3<f32>, frame: u64, requests: &mut Vec<(f32, ChunkKey)>) {
+        if self.beyond_horizon(key, eye) {
+            return;
+        }
+        if let Some(last_used) = self.last_used.get_mut(&key) {
+            *last_used = frame;
+        }
+
+        let distance = (eye - key.center()).magnitude();
+        if u32::from(key.level) < self.max_depth && distance < SPLIT_DISTANCE * key.angular_size() {
+            let children = key.children();
+            let missing: Vec<ChunkKey> =
+                children.iter().copied().filter(|child| !self.last_used.contains_key(child)).collect();
+            if missing.is_empty() {
+                for child in children {
+                    self.select(child, eye, frame, requests);
+                }
+                return;
+            }
+            requests.extend(missing.into_iter().map(|child| (distance, child)));
+        }
+        self.visible.push(key);
+    }
+
+    // Parche tapado por la curvatura del cuerpo, contando las montañas de ambos lados
+    fn beyond_horizon(&self, key: ChunkKey, eye: Vector3<f32>) -> bool {
+        let distance = eye.magnitude();
+        if distance <= 1.0 + self.relief {
+            return false;
+        }
+        let horizon = (1.0 / distance).acos() + (1.0 / (1.0 + self.relief)).acos();
+        let angle = (eye / distance).dot(key.center()).clamp(-1.0, 1.0).acos();
+        angle > horizon + key.angular_size() * 0.75
+    }
+}
+
+pub struct Terrain {
+    name: String,
+    material: MaterialParams,
+    quadtree: Quadtree,
+    chunks: HashMap<ChunkKey, wgpu::Buffer>,
+    frame: u64,
+}
+
+impl Terrain {
+    // Solo los cuerpos con superficie sólida tienen terreno
+    pub fn new(body: &BodyParams, params: &TerrainParams) -> Option<Self> {
+        if !matches!(body.material.kind, MaterialKind::Rocky | MaterialKind::Ocean) {
+            eprintln!("{}: el terreno solo se admite en cuerpos rocosos u oceánicos", body.name);
+            return None;
+        }
+        Some(Self {
+            name: body.name.clone(),
+            material: body.material.clone(),
+            quadtree: Quadtree {
+                relief: params.relief.max(0.0),
+                max_depth: params.max_depth.min(20),
+                last_used: HashMap::new(),
+                visible: Vec::new(),
+            },
+            chunks: HashMap::new(),
+            frame: 0,
+        })
+    }
+
+    // Radio que encierra las montañas más altas, en radios del cuerpo
+    pub fn max_radius(&self) -> f32 {
+        1.0 + self.quadtree.relief * 1.5
+    }
+
+    // El terreno reemplaza a la esfera mientras la cámara está cerca
+    pub fn is_active(&self) -> bool {
+        !self.quadtree.visible.is_empty()
+    }
+
+    // `eye` es la posición de la cámara en el marco del cuerpo (que gira con él), en radios
+    pub fn update(&mut self, device: &wgpu::Device, eye: Vector3<f32>) {
+        self.frame += 1;
+        self.quadtree.visible.clear();
+
+        if eye.magnitude() < TERRAIN_RANGE {
+            // Las seis raíces se generan juntas: siempre hay un parche que dibujar
+            for face in 0..FACES.len() as u8 {
+                let root = ChunkKey::root(face);
+                if !self.chunks.contains_key(&root) {
+                    self.generate(device, root);
+                }
+            }
+
+            let mut requests = Vec::new();
+            for face in 0..FACES.len() as u8 {
+                self.quadtree.select(ChunkKey::root(face), eye, self.frame, &mut requests);
+            }
+
+            // Primero los parches más cercanos a la cámara
+            requests.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+            for (_, key) in requests.into_iter().take(CHUNKS_PER_FRAME) {
+                self.generate(device, key);
+            }
+        }
+
+        let frame = self.frame;
+        self.quadtree
+            .last_used
+            .retain(|key, last_used| key.level == 0 || frame - *last_used < EVICT_FRAMES);
+        let last_used = &self.quadtree.last_used;
+        self.chunks.retain(|key, _| last_used.contains_key(key));
+    }
+
+    fn generate(&mut self, device: &wgpu::Device, key: ChunkKey) {
+        let vertices = generate_chunk(key, &self.material, self.quadtree.relief);
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Terrain Chunk {:?}", self.name, key)),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.chunks.insert(key, vertex_buffer);
+        self.quadtree.last_used.insert(key, self.frame);
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, pipeline: &'a TerrainPipeline) {
+        render_pass.set_index_buffer(pipeline.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        for key in &self.quadtree.visible {
+            if let Some(vertex_buffer) = self.chunks.get(key) {
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.draw_indexed(0..pipeline.num_indices, 0, 0..1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_triangles_face_outward() {
+        let material = MaterialParams::default();
+        let indices = chunk_indices();
+        let grid = CHUNK_RESOLUTION * CHUNK_RESOLUTION * 6;
+        for face in 0..FACES.len() as u8 {
+            let key = ChunkKey { face, level: 2, x: 1, y: 2 };
+            let vertices = generate_chunk(key, &material, 0.01);
+            assert_eq!(vertices.len(), (CHUNK_RESOLUTION + 1) * (CHUNK_RESOLUTION + 5));
+            for triangle in indices[..grid].chunks(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(vertices[triangle[i] as usize].position));
+                assert!((b - a).cross(c - a).dot(a) > 0.0);
+            }
+            for vertex in &vertices[..(CHUNK_RESOLUTION + 1) * (CHUNK_RESOLUTION + 1)] {
+                let position = Vector3::from(vertex.position);
+                assert!(Vector3::from(vertex.normal).dot(position) > 0.0);
+                assert!((position.magnitude() - 1.0).abs() <= 0.011);
+            }
+        }
+    }
+
+    #[test]
+    fn splits_only_near_the_camera() {
+        let mut quadtree = Quadtree {
+            relief: 0.01,
+            max_depth: 10,
+            last_used: HashMap::new(),
+            visible: Vec::new(),
+        };
+        for face in 0..FACES.len() as u8 {
+            let root = ChunkKey::root(face);
+            quadtree.last_used.insert(root, 0);
+            quadtree.last_used.extend(root.children().map(|child| (child, 0)));
+        }
+
+        // Lejos alcanza con los hijos de la raíz de la cara +Y
+        let mut requests = Vec::new();
+        quadtree.select(ChunkKey::root(2), Vector3::new(0.0, 2.9, 0.0), 1, &mut requests);
+        assert!(requests.is_empty());
+        assert_eq!(quadtree.visible, ChunkKey::root(2).children().to_vec());
+
+        // Cerca se usan sus hijos y se piden nietos bajo la cámara
+        quadtree.visible.clear();
+        quadtree.select(ChunkKey::root(2), Vector3::new(0.0, 1.05, 0.0), 2, &mut requests);
+        assert!(!requests.is_empty());
+        assert!(requests.iter().all(|(_, key)| key.face == 2 && key.level == 2));
+        assert!(quadtree.visible.iter().all(|key| key.level == 1));
+
+        // La cara opuesta queda bajo el horizonte
+        quadtree.visible.clear();
+        quadtree.select(ChunkKey::root(3), Vector3::new(0.0, 1.05, 0.0), 2, &mut requests);
+        assert!(quadtree.visible.is_empty());
+    }
+}